@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+// Benches can't reach into a binary crate, so they time the binary's own `--benchmark` mode
+
+// Writes `contents` to a config of its own under the target dir
+pub fn write_config(name: &str, contents: &str) -> PathBuf {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.toml", name));
+    fs::write(&path, contents).expect("failed to write the bench config");
+    path
+}
+
+// Runs `--benchmark` on `threads` threads, or every core if None, prints its report and
+// returns the wall time of the whole run
+pub fn run_benchmark(config: &Path, generations: u32, threads: Option<usize>) -> Duration {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_smart_rockets"));
+    command
+        .arg(config)
+        .args(["--benchmark", "--generations", &generations.to_string()]);
+    if let Some(threads) = threads {
+        command.env("RAYON_NUM_THREADS", threads.to_string());
+    }
+    let start = Instant::now();
+    let output = command.output().expect("failed to run smart_rockets");
+    let elapsed = start.elapsed();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    print!("{}", String::from_utf8_lossy(&output.stdout));
+    elapsed
+}
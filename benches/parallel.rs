@@ -0,0 +1,22 @@
+// Times 5000 rockets on one thread and on every core to show what flying and breeding them in
+// parallel buys
+mod common;
+
+const ROCKET_COUNT: usize = 5000;
+const GENERATIONS: u32 = 5;
+
+fn main() {
+    let config = common::write_config("parallel", &format!("rocket_count = {}\n", ROCKET_COUNT));
+    println!("-- 1 thread --");
+    let serial = common::run_benchmark(&config, GENERATIONS, Some(1));
+    println!("-- every core --");
+    let parallel = common::run_benchmark(&config, GENERATIONS, None);
+    println!(
+        "{} rockets, {} generations: {:.2?} on 1 thread, {:.2?} on every core, {:.2}x faster",
+        ROCKET_COUNT,
+        GENERATIONS,
+        serial,
+        parallel,
+        serial.as_secs_f64() / parallel.as_secs_f64()
+    );
+}
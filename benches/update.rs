@@ -0,0 +1,18 @@
+// Times `update` on enough rockets that rescanning every crashed or landed one each frame
+// would show up in its share of the report
+mod common;
+
+const ROCKET_COUNT: usize = 20000;
+const GENERATIONS: u32 = 3;
+
+fn main() {
+    let config = common::write_config(
+        "update",
+        &format!("rocket_count = {}\ngene_len = 200\n", ROCKET_COUNT),
+    );
+    let total = common::run_benchmark(&config, GENERATIONS, None);
+    println!(
+        "{} rockets, {} generations in {:.2?}",
+        ROCKET_COUNT, GENERATIONS, total
+    );
+}
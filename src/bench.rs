@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+// Time a benchmark run spent in each part of the simulation, `restart` includes the three
+// phases after it
+#[derive(Default)]
+pub struct PhaseTimes {
+    pub restart: Duration,
+    pub fitness: Duration,
+    pub mating_pool: Duration,
+    pub selection: Duration,
+}
+
+impl PhaseTimes {
+    pub fn report(&self, total: Duration, generations: u32, frames: u64) {
+        let secs = total.as_secs_f64();
+        println!("Generations: {}", generations);
+        println!("Frames: {}", frames);
+        println!("Wall time: {:.3} s", secs);
+        println!("Generations/sec: {:.2}", generations as f64 / secs);
+        println!("Frames/sec: {:.0}", frames as f64 / secs);
+
+        let other_restart = self
+            .restart
+            .saturating_sub(self.fitness + self.mating_pool + self.selection);
+        let phases = [
+            ("update", total.saturating_sub(self.restart)),
+            ("calc_fitness", self.fitness),
+            ("mating pool", self.mating_pool),
+            ("selection", self.selection),
+            ("rest of restart", other_restart),
+        ];
+        for (name, time) in phases {
+            println!(
+                "  {:<16} {:>9.3} ms  {:>5.1}%",
+                name,
+                time.as_secs_f64() * 1000.0,
+                time.as_secs_f64() / secs * 100.0
+            );
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::str::FromStr;
+
+use crate::config::DEFAULT_CONFIG_PATH;
+
+pub struct Args {
+    pub config_path: String,
+    pub seed: Option<u64>,
+    // Number of generations to run without a window, if running headless
+    pub headless_generations: Option<u32>,
+    // Number of generations every parameter combination of a sweep runs for
+    pub sweep_generations: Option<u32>,
+    // Number of generations a benchmark times the simulation for
+    pub benchmark_generations: Option<u32>,
+    // Generation to close the window at
+    pub max_generations: Option<u32>,
+    // Saved generation to fly again and again instead of evolving
+    pub replay_path: Option<String>,
+    // Seconds between saves of the whole simulation
+    pub autosave_secs: Option<f32>,
+    // Saved simulation to carry on from instead of starting from the config
+    pub resume_path: Option<String>,
+    // Start the window without vsync
+    pub uncapped: bool,
+    // Directory of saved genomes planted into the first generation
+    pub init_from_dir: Option<String>,
+    // Seed and config of a second world shown next to the first, either one turns it on
+    pub compare_seed: Option<u64>,
+    pub compare_config_path: Option<String>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        let mut args = Self {
+            config_path: DEFAULT_CONFIG_PATH.to_string(),
+            seed: None,
+            headless_generations: None,
+            sweep_generations: None,
+            benchmark_generations: None,
+            max_generations: None,
+            replay_path: None,
+            autosave_secs: None,
+            resume_path: None,
+            uncapped: false,
+            init_from_dir: None,
+            compare_seed: None,
+            compare_config_path: None,
+        };
+        let mut headless = false;
+        let mut sweep = false;
+        let mut benchmark = false;
+        let mut generations = None;
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--seed" => args.seed = Some(parse_value(&arg, iter.next())?),
+                "--headless" => headless = true,
+                "--sweep" => sweep = true,
+                "--benchmark" => benchmark = true,
+                "--uncapped" => args.uncapped = true,
+                "--generations" => generations = Some(parse_value(&arg, iter.next())?),
+                "--max-generations" => args.max_generations = Some(parse_value(&arg, iter.next())?),
+                "--replay" => args.replay_path = Some(parse_value(&arg, iter.next())?),
+                "--autosave" => args.autosave_secs = Some(parse_value(&arg, iter.next())?),
+                "--resume" => args.resume_path = Some(parse_value(&arg, iter.next())?),
+                "--init-from" => args.init_from_dir = Some(parse_value(&arg, iter.next())?),
+                "--compare-seed" => args.compare_seed = Some(parse_value(&arg, iter.next())?),
+                "--compare-config" => {
+                    args.compare_config_path = Some(parse_value(&arg, iter.next())?)
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown flag '{}'", arg)),
+                _ => args.config_path = arg,
+            }
+        }
+        if headless && sweep {
+            return Err("'--sweep' already runs headless, drop '--headless'".to_string());
+        }
+        if benchmark && (headless || sweep) {
+            return Err(
+                "'--benchmark' already runs headless and can't be combined with '--sweep'"
+                    .to_string(),
+            );
+        }
+        if benchmark
+            && (args.max_generations.is_some()
+                || args.replay_path.is_some()
+                || args.autosave_secs.is_some()
+                || args.resume_path.is_some()
+                || args.compare())
+        {
+            return Err(
+                "'--benchmark' only times the config, drop '--max-generations', '--replay', \
+                 '--autosave', '--resume' and '--compare-*'"
+                    .to_string(),
+            );
+        }
+        if sweep
+            && (args.max_generations.is_some()
+                || args.replay_path.is_some()
+                || args.autosave_secs.is_some()
+                || args.resume_path.is_some())
+        {
+            return Err(
+                "'--sweep' can't be combined with '--max-generations', '--replay', '--autosave' \
+                 or '--resume'"
+                    .to_string(),
+            );
+        }
+        if args
+            .autosave_secs
+            .is_some_and(|secs| !(secs > 0.0 && secs.is_finite()))
+        {
+            return Err("'--autosave' needs a positive number of seconds".to_string());
+        }
+        // The saved world already holds whatever it was replaying
+        if args.resume_path.is_some() && args.replay_path.is_some() {
+            return Err("'--resume' can't be combined with '--replay'".to_string());
+        }
+        // Sweeps and benchmarks compare random starts, a resume or replay already decides
+        // every genome of the first generation
+        if args.init_from_dir.is_some()
+            && (sweep || benchmark || args.resume_path.is_some() || args.replay_path.is_some())
+        {
+            return Err(
+                "'--init-from' can't be combined with '--sweep', '--benchmark', '--resume' or \
+                 '--replay'"
+                    .to_string(),
+            );
+        }
+        if args.uncapped && (headless || sweep || benchmark) {
+            return Err(
+                "'--uncapped' only changes the window, which a headless run doesn't have"
+                    .to_string(),
+            );
+        }
+        if args.compare()
+            && (headless || sweep || args.replay_path.is_some() || args.resume_path.is_some())
+        {
+            return Err(
+                "'--compare-seed' and '--compare-config' need a window and can't be combined \
+                 with '--replay' or '--resume'"
+                    .to_string(),
+            );
+        }
+        match (headless || sweep || benchmark, generations) {
+            (true, None) => {
+                return Err(
+                    "'--headless', '--sweep' and '--benchmark' require '--generations <N>'"
+                        .to_string(),
+                )
+            }
+            (false, Some(_)) => {
+                return Err(
+                    "'--generations' requires '--headless', '--sweep' or '--benchmark'".to_string(),
+                )
+            }
+            _ if sweep => args.sweep_generations = generations,
+            _ if benchmark => args.benchmark_generations = generations,
+            _ => args.headless_generations = generations,
+        }
+        if headless && args.max_generations.is_some() {
+            return Err(
+                "use '--generations' instead of '--max-generations' with '--headless'".to_string(),
+            );
+        }
+        // A replay never reaches a new generation, so a headless run would never end
+        if headless && args.replay_path.is_some() {
+            return Err("'--replay' can't be combined with '--headless'".to_string());
+        }
+        Ok(args)
+    }
+
+    pub fn compare(&self) -> bool {
+        self.compare_seed.is_some() || self.compare_config_path.is_some()
+    }
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing value for '{}'", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for '{}'", value, flag))
+}
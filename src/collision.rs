@@ -0,0 +1,221 @@
+use raylib::prelude::*;
+
+use crate::{Rocket, ROCKET_SIZE};
+
+// Corners of the rocket's body as `render` draws it, rotated around its position
+pub fn rocket_obb(rocket: &Rocket) -> [Vector2; 4] {
+    let body_angle = (rocket.angle + 90.0).to_radians();
+    let (sin, cos) = body_angle.sin_cos();
+    let (half_width, half_height) = (ROCKET_SIZE.x / 2.0, ROCKET_SIZE.y / 2.0);
+    [
+        (-half_width, -half_height),
+        (half_width, -half_height),
+        (half_width, half_height),
+        (-half_width, half_height),
+    ]
+    .map(|(x, y)| {
+        Vector2::new(
+            rocket.pos.x + x * cos - y * sin,
+            rocket.pos.y + x * sin + y * cos,
+        )
+    })
+}
+
+// Separating axis test between an oriented box given by its corners in order and an
+// axis-aligned rectangle. Only the box's two edge directions and the rectangle's two axes
+// can separate them, so they overlap unless one of those four splits them apart
+pub fn obb_overlaps_rect(corners: &[Vector2; 4], rect: &Rectangle) -> bool {
+    let rect_corners = rect_corners(rect);
+    let axes = [
+        Vector2::new(1.0, 0.0),
+        Vector2::new(0.0, 1.0),
+        corners[1] - corners[0],
+        corners[3] - corners[0],
+    ];
+    axes.iter().all(|axis| {
+        let (min_a, max_a) = project(corners, *axis);
+        let (min_b, max_b) = project(&rect_corners, *axis);
+        min_a <= max_b && min_b <= max_a
+    })
+}
+
+fn project(corners: &[Vector2], axis: Vector2) -> (f32, f32) {
+    corners
+        .iter()
+        .map(|corner| corner.dot(axis))
+        .fold((f32::MAX, f32::MIN), |(min, max), value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+// Separating axis test between two convex polygons, returns the shortest move that takes `a`
+// out of `b` when they overlap. Only the edge normals of either shape can separate them
+pub fn convex_push_out(a: &[Vector2], b: &[Vector2]) -> Option<Vector2> {
+    let mut push: Option<(Vector2, f32)> = None;
+    for polygon in [a, b] {
+        for (ind, &start) in polygon.iter().enumerate() {
+            let edge = polygon[(ind + 1) % polygon.len()] - start;
+            if edge.length() == 0.0 {
+                continue;
+            }
+            let axis = Vector2::new(-edge.y, edge.x).normalized();
+            let (min_a, max_a) = project(a, axis);
+            let (min_b, max_b) = project(b, axis);
+            let (forward, back) = (max_b - min_a, max_a - min_b);
+            if forward <= 0.0 || back <= 0.0 {
+                return None;
+            }
+            let (depth, dir) = if forward < back {
+                (forward, axis)
+            } else {
+                (back, -axis)
+            };
+            if push.is_none_or(|(_, best)| depth < best) {
+                push = Some((dir * depth, depth));
+            }
+        }
+    }
+    push.map(|(push, _)| push)
+}
+
+pub fn polygon_bounds(polygon: &[Vector2]) -> Rectangle {
+    let (min_x, max_x) = project(polygon, Vector2::new(1.0, 0.0));
+    let (min_y, max_y) = project(polygon, Vector2::new(0.0, 1.0));
+    Rectangle::new(min_x, min_y, max_x - min_x, max_y - min_y)
+}
+
+pub fn rect_corners(rect: &Rectangle) -> [Vector2; 4] {
+    [
+        Vector2::new(rect.x, rect.y),
+        Vector2::new(rect.x + rect.width, rect.y),
+        Vector2::new(rect.x + rect.width, rect.y + rect.height),
+        Vector2::new(rect.x, rect.y + rect.height),
+    ]
+}
+
+// Twice the signed area, negative when the points run counter-clockwise on screen
+pub fn signed_area(polygon: &[Vector2]) -> f32 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum()
+}
+
+// Ignores repeated and collinear points, either winding counts
+pub fn is_convex(polygon: &[Vector2]) -> bool {
+    let len = polygon.len();
+    let turns = (0..len).map(|ind| {
+        let (a, b, c) = (
+            polygon[ind],
+            polygon[(ind + 1) % len],
+            polygon[(ind + 2) % len],
+        );
+        let (ab, bc) = (b - a, c - b);
+        ab.x * bc.y - ab.y * bc.x
+    });
+    let (mut left, mut right) = (false, false);
+    for turn in turns {
+        left |= turn > 0.0;
+        right |= turn < 0.0;
+    }
+    !(left && right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GeneEncoding;
+
+    fn rocket_at(pos: Vector2, angle: f32) -> Rocket {
+        Rocket::new(pos, angle, 1, GeneEncoding::Angle, 1, 1.0)
+    }
+
+    #[test]
+    fn body_hits_wall_its_center_misses() {
+        // Pointing up, the body reaches 7.5 px either side of its center
+        let rocket = rocket_at(Vector2::new(100.0, 100.0), -90.0);
+        let wall = Rectangle::new(105.0, 80.0, 100.0, 40.0);
+        assert!(!wall.check_collision_point_rec(rocket.pos));
+        assert!(obb_overlaps_rect(&rocket_obb(&rocket), &wall));
+    }
+
+    #[test]
+    fn rotated_body_touches_wall() {
+        // Tilted 45 degrees, one corner of the nose sits at about (121.2, 89.4)
+        let rocket = rocket_at(Vector2::new(100.0, 100.0), -45.0);
+        let wall = Rectangle::new(118.0, 80.0, 100.0, 20.0);
+        assert!(!wall.check_collision_point_rec(rocket.pos));
+        assert!(obb_overlaps_rect(&rocket_obb(&rocket), &wall));
+    }
+
+    #[test]
+    fn rotated_body_near_miss() {
+        // Inside the box around the tilted body, but off to the side of the body itself
+        let rocket = rocket_at(Vector2::new(100.0, 100.0), -45.0);
+        let wall = Rectangle::new(112.0, 112.0, 8.0, 8.0);
+        assert!(wall.check_collision_recs(&rocket.bounding_box()));
+        assert!(!obb_overlaps_rect(&rocket_obb(&rocket), &wall));
+    }
+
+    fn square(x: f32, y: f32, size: f32) -> [Vector2; 4] {
+        [
+            Vector2::new(x, y),
+            Vector2::new(x + size, y),
+            Vector2::new(x + size, y + size),
+            Vector2::new(x, y + size),
+        ]
+    }
+
+    // Square of side about 14 turned 45 degrees around the origin
+    fn diamond() -> [Vector2; 4] {
+        [
+            Vector2::new(0.0, -10.0),
+            Vector2::new(10.0, 0.0),
+            Vector2::new(0.0, 10.0),
+            Vector2::new(-10.0, 0.0),
+        ]
+    }
+
+    #[test]
+    fn axis_aligned_boxes() {
+        let corners = square(0.0, 0.0, 10.0);
+        assert!(obb_overlaps_rect(
+            &corners,
+            &Rectangle::new(5.0, 5.0, 10.0, 10.0)
+        ));
+        assert!(!obb_overlaps_rect(
+            &corners,
+            &Rectangle::new(20.0, 0.0, 10.0, 10.0)
+        ));
+    }
+
+    #[test]
+    fn rotated_box_overlaps_rect() {
+        assert!(obb_overlaps_rect(
+            &diamond(),
+            &Rectangle::new(5.0, -2.0, 10.0, 4.0)
+        ));
+    }
+
+    #[test]
+    fn rotated_box_separated_only_by_its_edge_normal() {
+        // Overlaps on both screen axes, only the diamond's x + y = 10 edge splits them
+        let rect = Rectangle::new(8.0, 8.0, 10.0, 10.0);
+        let corners = diamond();
+        assert!(rect.check_collision_recs(&Rectangle::new(-10.0, -10.0, 20.0, 20.0)));
+        assert!(!obb_overlaps_rect(&corners, &rect));
+    }
+
+    #[test]
+    fn one_shape_inside_the_other() {
+        assert!(obb_overlaps_rect(
+            &diamond(),
+            &Rectangle::new(-2.0, -2.0, 4.0, 4.0)
+        ));
+        assert!(obb_overlaps_rect(
+            &diamond(),
+            &Rectangle::new(-50.0, -50.0, 100.0, 100.0)
+        ));
+    }
+}
@@ -0,0 +1,508 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision;
+use crate::map::MapLayout;
+use crate::serde_helpers::{
+    circle_vec, polygon_vec, rectangle_vec, vector2_opt, vector2_vec, Vector2Def,
+};
+use crate::{
+    BoundaryCheck, BoundaryMode, Catastrophe, CrossoverKind, DegreeSchedule, DifficultyRamp,
+    FitnessKind, Gate, GeneEncoding, InitDistribution, LengthMutation, MotionModel, MutationKind,
+    PopulationSchedule, SelectionStrategy, StateWeights, TargetMotion, DEGREE_CHANGE, GENE_LEN,
+    MUTATION_RATE, ROCKET_COUNT, ROCKET_SPEED, ROCKET_THRUST, SCREEN_HEIGHT, SCREEN_WIDTH,
+    TARGET_RADIUS, WALL_SIZE,
+};
+
+pub const DEFAULT_CONFIG_PATH: &str = "./config.toml";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimConfig {
+    pub gene_len: usize,
+    pub rocket_count: usize,
+    // Grows or shrinks the population between generations, `rocket_count` is overwritten with
+    // the scheduled size at every restart
+    pub population_schedule: PopulationSchedule,
+    pub mutation_rate: f32,
+    pub rocket_speed: f32,
+    pub degree_change: f32,
+    // Lets the largest turn change along the genome, e.g. bold early turns and fine late ones
+    pub degree_schedule: DegreeSchedule,
+    // Most an angle-encoded rocket turns in a frame, in degrees
+    pub max_turn_rate: f32,
+    // Angle genes add to a spin the rocket keeps turning with, rather than turning it directly
+    pub angular_momentum: bool,
+    // Wind or gravity added to every rocket's motion each frame, an offset under the constant
+    // speed model and an acceleration otherwise
+    #[serde(with = "Vector2Def")]
+    pub environment_force: Vector2,
+    // Frames a rocket can fly for, defaults to `gene_len`
+    pub fuel: Option<u32>,
+    // Rockets bounce off walls until the damage from hitting them adds up to their health
+    pub rocket_health: f32,
+    pub wall_damage: f32,
+    pub gene_encoding: GeneEncoding,
+    pub init_distribution: InitDistribution,
+    pub motion_model: MotionModel,
+    // Acceleration per frame under the thrust model and the largest force gene,
+    // `rocket_speed` caps the velocity
+    pub thrust: f32,
+    pub crossover: CrossoverKind,
+    // Chance a child is bred by crossover, otherwise it starts as a copy of its first parent
+    pub crossover_rate: f32,
+    pub mutation: MutationKind,
+    // Lets mutation insert and delete genes, none keeps every genome at `gene_len`
+    pub length_mutation: Option<LengthMutation>,
+    // Fresh genes for part of a population that has stopped improving, none turns it off
+    pub catastrophe: Option<Catastrophe>,
+    pub selection: SelectionStrategy,
+    // Fittest rockets copied unchanged into the next generation, 0 breeds every child
+    pub elite_count: usize,
+    // Scale the roulette mating pool share of dead, still flying and successful rockets
+    pub state_weights: StateWeights,
+    // Second population that races the first one in the same arena, none runs just one
+    pub rival_selection: Option<SelectionStrategy>,
+    // Share of a generation that has to reach the final target for the map to count as solved
+    pub solved_success_rate: f32,
+    // Moves the final target somewhere harder every time the map is solved
+    pub difficulty_ramp: Option<DifficultyRamp>,
+    // Formula that turns the distance to the target into fitness
+    pub fitness_kind: FitnessKind,
+    // Fitness taken off per pixel flown, 0 leaves the fitness alone
+    pub path_length_penalty: f32,
+    // Measure the distance to a target around the walls instead of in a straight line
+    pub path_distance: bool,
+    pub screen_width: i32,
+    pub screen_height: i32,
+    // Rockets have to visit these in order, the last one counts as success
+    #[serde(with = "vector2_vec")]
+    pub targets: Vec<Vector2>,
+    // How close to a target's center a rocket has to get to reach it
+    pub target_radius: f32,
+    pub target_motion: TargetMotion,
+    // Where rockets launch from, defaults to just above the bottom middle of the screen
+    #[serde(with = "vector2_opt")]
+    pub spawn: Option<Vector2>,
+    // Heading rockets launch with in degrees, -90 points straight up
+    pub spawn_angle: f32,
+    // ASCII map whose walls, target and spawn replace the ones given here
+    pub map_path: Option<String>,
+    pub boundary_mode: BoundaryMode,
+    pub boundary_check: BoundaryCheck,
+    #[serde(with = "rectangle_vec")]
+    pub walls: Vec<Rectangle>,
+    // Convex walls given by their vertices in order, for diagonal barriers
+    #[serde(with = "polygon_vec")]
+    pub polygon_walls: Vec<Vec<Vector2>>,
+    #[serde(with = "circle_vec")]
+    pub circle_obstacles: Vec<(Vector2, f32)>,
+    pub gates: Vec<Gate>,
+    pub stats_path: Option<String>,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            gene_len: GENE_LEN,
+            rocket_count: ROCKET_COUNT,
+            population_schedule: PopulationSchedule::Constant,
+            mutation_rate: MUTATION_RATE,
+            rocket_speed: ROCKET_SPEED,
+            degree_change: DEGREE_CHANGE,
+            degree_schedule: DegreeSchedule::Flat,
+            max_turn_rate: 360.0,
+            angular_momentum: false,
+            environment_force: Vector2::zero(),
+            fuel: None,
+            rocket_health: 1.0,
+            wall_damage: 1.0,
+            gene_encoding: GeneEncoding::Angle,
+            init_distribution: InitDistribution::Uniform,
+            motion_model: MotionModel::ConstantSpeed,
+            thrust: ROCKET_THRUST,
+            crossover: CrossoverKind::OnePoint,
+            crossover_rate: 1.0,
+            mutation: MutationKind::Replace,
+            length_mutation: None,
+            catastrophe: None,
+            selection: SelectionStrategy::Roulette,
+            elite_count: 0,
+            state_weights: StateWeights::default(),
+            rival_selection: None,
+            solved_success_rate: 0.9,
+            difficulty_ramp: None,
+            fitness_kind: FitnessKind::Inverse,
+            path_length_penalty: 0.0,
+            path_distance: false,
+            screen_width: SCREEN_WIDTH,
+            screen_height: SCREEN_HEIGHT,
+            targets: vec![Vector2::new(100.0, 100.0)],
+            target_radius: TARGET_RADIUS,
+            target_motion: TargetMotion::Static,
+            spawn: None,
+            spawn_angle: -90.0,
+            map_path: None,
+            boundary_mode: BoundaryMode::Kill,
+            boundary_check: BoundaryCheck::Center,
+            walls: vec![
+                Rectangle::new(300.0, 250.0, WALL_SIZE.x, WALL_SIZE.y),
+                Rectangle::new(150.0, 300.0, WALL_SIZE.x, WALL_SIZE.y),
+            ],
+            polygon_walls: vec![],
+            circle_obstacles: vec![],
+            gates: vec![],
+            stats_path: None,
+        }
+    }
+}
+
+impl SimConfig {
+    pub fn fuel(&self) -> u32 {
+        self.fuel.unwrap_or(self.max_gene_len() as u32)
+    }
+
+    // Most genes any genome can have
+    pub fn max_gene_len(&self) -> usize {
+        self.length_mutation
+            .map_or(self.gene_len, |length| length.max_len)
+    }
+
+    // Largest turn an angle gene at `ind` can hold, in degrees
+    pub fn degree_bound(&self, ind: usize) -> f32 {
+        match self.degree_schedule {
+            DegreeSchedule::Flat => self.degree_change,
+            DegreeSchedule::Linear { start, end } => {
+                let t = (ind as f32 / (self.gene_len.max(2) - 1) as f32).min(1.0);
+                start + (end - start) * t
+            }
+        }
+    }
+
+    // Rockets that fly in `generation`, counting from 0
+    pub fn population_size(&self, generation: u32) -> usize {
+        match self.population_schedule {
+            PopulationSchedule::Constant => self.rocket_count,
+            PopulationSchedule::Linear {
+                start,
+                end,
+                generations,
+            } => {
+                let t = (generation as f32 / generations as f32).min(1.0);
+                (start as f32 + (end as f32 - start as f32) * t).round() as usize
+            }
+        }
+    }
+
+    pub fn spawn(&self) -> Vector2 {
+        self.spawn.unwrap_or(Vector2::new(
+            (self.screen_width / 2) as f32,
+            (self.screen_height - 75) as f32,
+        ))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                println!("WARNING: config file '{}' not found, using defaults", path);
+                return Ok(Self::default());
+            }
+            Err(err) => return Err(format!("failed to read '{}': {}", path, err)),
+        };
+        let mut config: Self = toml::from_str(&contents)
+            .map_err(|err| format!("failed to parse '{}': {}", path, err))?;
+        if let Some(map_path) = &config.map_path {
+            let map = MapLayout::load(
+                map_path,
+                config.screen_width as f32,
+                config.screen_height as f32,
+            )?;
+            config.walls = map.walls;
+            config.targets = vec![map.target];
+            config.spawn = Some(map.spawn);
+        }
+        config.validate()?;
+        // Drawing them as a triangle fan needs the vertices counter-clockwise on screen
+        for polygon in &mut config.polygon_walls {
+            if collision::signed_area(polygon) > 0.0 {
+                polygon.reverse();
+            }
+        }
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.gene_len == 0 {
+            return Err("gene_len must be at least 1".to_string());
+        }
+        if self.rocket_count == 0 {
+            return Err("rocket_count must be at least 1".to_string());
+        }
+        if let PopulationSchedule::Linear {
+            start,
+            end,
+            generations,
+        } = self.population_schedule
+        {
+            if start == 0 || end == 0 {
+                return Err("population_schedule start and end must be at least 1".to_string());
+            }
+            if generations == 0 {
+                return Err("population_schedule generations must be at least 1".to_string());
+            }
+        }
+        if !self.degree_change.is_finite() || self.degree_change <= 0.0 {
+            return Err("degree_change must be positive".to_string());
+        }
+        let smallest_population = match self.population_schedule {
+            PopulationSchedule::Constant => self.rocket_count,
+            PopulationSchedule::Linear { start, end, .. } => start.min(end),
+        };
+        if self.elite_count > smallest_population {
+            return Err(format!(
+                "elite_count {} can't be more than the {} rockets of the smallest generation",
+                self.elite_count, smallest_population
+            ));
+        }
+        if let DegreeSchedule::Linear { start, end } = self.degree_schedule {
+            if start <= 0.0 || end <= 0.0 {
+                return Err("degree_schedule start and end must be positive".to_string());
+            }
+        }
+        if self.max_turn_rate <= 0.0 {
+            return Err("max_turn_rate must be positive".to_string());
+        }
+        if !(self.environment_force.x.is_finite() && self.environment_force.y.is_finite()) {
+            return Err("environment_force has to be finite".to_string());
+        }
+        if self.rocket_health <= 0.0 {
+            return Err("rocket_health must be positive".to_string());
+        }
+        if self.wall_damage < 0.0 {
+            return Err("wall_damage can't be negative".to_string());
+        }
+        if !(self.solved_success_rate > 0.0 && self.solved_success_rate <= 1.0) {
+            return Err(format!(
+                "solved_success_rate {} must be above 0 and at most 1",
+                self.solved_success_rate
+            ));
+        }
+        if self.path_length_penalty < 0.0 {
+            return Err("path_length_penalty can't be negative".to_string());
+        }
+        if let InitDistribution::Gaussian { std } = self.init_distribution {
+            if std <= 0.0 {
+                return Err("init_distribution std must be positive".to_string());
+            }
+        }
+        for strategy in std::iter::once(self.selection).chain(self.rival_selection) {
+            if let SelectionStrategy::Tournament { k } = strategy {
+                if k == 0 {
+                    return Err("tournament selection needs k of at least 1".to_string());
+                }
+            }
+        }
+        // The distance field is only rebuilt when the map or a target changes, a target
+        // moving every frame would rebuild it every frame
+        if self.path_distance && !matches!(self.target_motion, TargetMotion::Static) {
+            return Err("path_distance can't be combined with a moving target_motion".to_string());
+        }
+        let weights = self.state_weights;
+        if [weights.dead, weights.timeout, weights.successful]
+            .iter()
+            .any(|&weight| weight < 0.0)
+        {
+            return Err("state_weights can't be negative".to_string());
+        }
+        if let MutationKind::Gaussian { std } = self.mutation {
+            if std <= 0.0 {
+                return Err("mutation std must be positive".to_string());
+            }
+        }
+        if let Some(catastrophe) = self.catastrophe {
+            if catastrophe.generations == 0 {
+                return Err("catastrophe generations must be at least 1".to_string());
+            }
+            if !(0.0..=1.0).contains(&catastrophe.fraction) {
+                return Err(format!(
+                    "catastrophe fraction {} must be between 0 and 1",
+                    catastrophe.fraction
+                ));
+            }
+        }
+        if let Some(length) = self.length_mutation {
+            if !(0.0..=1.0).contains(&length.rate) {
+                return Err(format!(
+                    "length_mutation rate {} must be between 0 and 1",
+                    length.rate
+                ));
+            }
+            if !(1..=self.gene_len).contains(&length.min_len) || length.max_len < self.gene_len {
+                return Err(format!(
+                    "length_mutation needs 1 <= min_len <= gene_len <= max_len, got {} <= {} <= {}",
+                    length.min_len, self.gene_len, length.max_len
+                ));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.crossover_rate) {
+            return Err(format!(
+                "crossover_rate {} must be between 0 and 1",
+                self.crossover_rate
+            ));
+        }
+        // Also the largest force gene, so random genes are drawn from 0 up to it
+        if !self.thrust.is_finite() || self.thrust <= 0.0 {
+            return Err("thrust must be positive".to_string());
+        }
+        if self.screen_width <= 0 || self.screen_height <= 0 {
+            return Err(format!(
+                "screen size {}x{} must be positive",
+                self.screen_width, self.screen_height
+            ));
+        }
+        let (width, height) = (self.screen_width as f32, self.screen_height as f32);
+        if self.targets.is_empty() {
+            return Err("at least one target is required".to_string());
+        }
+        for (ind, target) in self.targets.iter().enumerate() {
+            if target.x < 0.0 || target.y < 0.0 || target.x > width || target.y > height {
+                return Err(format!(
+                    "target {} (x: {}, y: {}) is not inside the {}x{} screen",
+                    ind, target.x, target.y, width, height
+                ));
+            }
+        }
+        if !(self.target_radius > 0.0 && self.target_radius.is_finite()) {
+            return Err(format!(
+                "target_radius {} must be positive",
+                self.target_radius
+            ));
+        }
+        match &self.difficulty_ramp {
+            Some(DifficultyRamp::Targets { targets }) => {
+                for (ind, target) in targets.iter().enumerate() {
+                    if target.x < 0.0 || target.y < 0.0 || target.x > width || target.y > height {
+                        return Err(format!(
+                            "difficulty_ramp target {} (x: {}, y: {}) is not inside the {}x{} \
+                             screen",
+                            ind, target.x, target.y, width, height
+                        ));
+                    }
+                }
+            }
+            Some(DifficultyRamp::Distance { step }) if *step <= 0.0 => {
+                return Err("difficulty_ramp step must be positive".to_string());
+            }
+            _ => {}
+        }
+        let spawn = self.spawn();
+        if spawn.x < 0.0 || spawn.y < 0.0 || spawn.x > width || spawn.y > height {
+            return Err(format!(
+                "spawn (x: {}, y: {}) is not inside the {}x{} screen",
+                spawn.x, spawn.y, width, height
+            ));
+        }
+        for (ind, wall) in self.walls.iter().enumerate() {
+            if wall.x < 0.0
+                || wall.y < 0.0
+                || wall.width <= 0.0
+                || wall.height <= 0.0
+                || wall.x + wall.width > width
+                || wall.y + wall.height > height
+            {
+                return Err(format!(
+                    "wall {} (x: {}, y: {}, width: {}, height: {}) is not inside the {}x{} screen",
+                    ind, wall.x, wall.y, wall.width, wall.height, width, height
+                ));
+            }
+        }
+        for (ind, polygon) in self.polygon_walls.iter().enumerate() {
+            if polygon.len() < 3 {
+                return Err(format!("polygon wall {} needs at least 3 vertices", ind));
+            }
+            if let Some(vertex) = polygon
+                .iter()
+                .find(|el| el.x < 0.0 || el.y < 0.0 || el.x > width || el.y > height)
+            {
+                return Err(format!(
+                    "polygon wall {} vertex (x: {}, y: {}) is not inside the {}x{} screen",
+                    ind, vertex.x, vertex.y, width, height
+                ));
+            }
+            if !collision::is_convex(polygon) || collision::signed_area(polygon) == 0.0 {
+                return Err(format!("polygon wall {} has to be convex", ind));
+            }
+        }
+        for (ind, (center, radius)) in self.circle_obstacles.iter().enumerate() {
+            if *radius <= 0.0 {
+                return Err(format!(
+                    "circle obstacle {} (x: {}, y: {}) needs a positive radius",
+                    ind, center.x, center.y
+                ));
+            }
+        }
+        for (ind, gate) in self.gates.iter().enumerate() {
+            if gate.period == 0 {
+                return Err(format!("gate {} needs a period of at least 1 frame", ind));
+            }
+            if !(0.0..=1.0).contains(&gate.open_fraction) {
+                return Err(format!(
+                    "gate {} open_fraction {} must be between 0 and 1",
+                    ind, gate.open_fraction
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(SimConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_degree_change_that_isnt_positive() {
+        for degree_change in [0.0, -5.0, f32::NAN] {
+            let config = SimConfig {
+                degree_change,
+                ..SimConfig::default()
+            };
+            assert!(config.validate().is_err(), "accepted {}", degree_change);
+        }
+    }
+
+    #[test]
+    fn rejects_thrust_that_isnt_positive() {
+        for thrust in [0.0, -0.2, f32::NAN] {
+            let config = SimConfig {
+                gene_encoding: GeneEncoding::Force,
+                thrust,
+                ..SimConfig::default()
+            };
+            assert!(config.validate().is_err(), "accepted {}", thrust);
+        }
+    }
+
+    #[test]
+    fn rejects_path_distance_to_a_moving_target() {
+        let config = SimConfig {
+            path_distance: true,
+            target_motion: TargetMotion::Circle {
+                center: Vector2::new(200.0, 150.0),
+                radius: 80.0,
+                speed: 0.02,
+            },
+            ..SimConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
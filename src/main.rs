@@ -1,11 +1,50 @@
-use std::ops::Sub;
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::fs;
+use std::ops::{Range, Sub};
+use std::time::{Duration, Instant};
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use raylib::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+mod bench;
+mod cli;
+mod collision;
+mod config;
+mod map;
+mod navgrid;
+mod serde_helpers;
+mod spatial;
+mod stats;
+
+use bench::PhaseTimes;
+use cli::Args;
+use config::SimConfig;
+use navgrid::NavGrid;
+use serde_helpers::{
+    circle_vec, polygon_vec, rectangle_vec, vector2_opt, vector2_vec, ColorDef, RectangleDef,
+    Vector2Def,
+};
+use spatial::SpatialGrid;
+use stats::{GenerationStats, StatsWriter};
 
 const GENE_LEN: usize = 400;
+const BEST_GENOME_PATH: &str = "best_genome.json";
+const GENERATION_PATH: &str = "generation.json";
+const WORLD_PATH: &str = "world.json";
 const MUTATION_RATE: f32 = 0.03;
+const STAGNANT_MUTATION_RATE: f32 = 0.15;
+const STAGNATION_GENERATIONS: usize = 10;
+const HISTORY_LEN: usize = 20;
+const DEATH_HISTORY_LEN: usize = 20;
+const FROZEN_PATHS_LEN: usize = 15;
+const DIVERSITY_SAMPLE_PAIRS: usize = 300;
 const DEGREE_CHANGE: f32 = 10.0;
+// Spread in degrees of how far a mutant's lineage hue moves away from its parent's
+const LINEAGE_HUE_DRIFT: f32 = 4.0;
 
 const SCREEN_WIDTH: i32 = 1000;
 const SCREEN_HEIGHT: i32 = 650;
@@ -13,374 +52,4717 @@ const BACKGROUND_COLOR: Color = Color::new(24, 24, 24, 255);
 
 const ROCKET_COUNT: usize = 80;
 const ROCKET_SPEED: f32 = 3.0;
+const ROCKET_THRUST: f32 = 0.2;
 const ROCKET_SIZE: Vector2 = Vector2::new(15.0, 45.0);
 const ALIVE_ROCKET_COLOR: Color = Color::new(230, 230, 230, 255);
 const DEAD_ROCKET_COLOR: Color = Color::new(230, 230, 230, 180); // 180 alpha value = 70.59% opacity
 const SUCCESSFUL_ROCKET_COLOR: Color = Color::new(230, 138, 80, 255);
+const TIMEOUT_ROCKET_COLOR: Color = Color::new(210, 200, 110, 255);
+const RIVAL_ROCKET_COLOR: Color = Color::new(90, 170, 250, 255);
+const TRAIL_LEN: usize = 60;
+// Long exposure trails: every frame the canvas is dimmed by a background colored rect this
+// opaque, then each flying rocket leaves a dot this big and this opaque on it
+const SPRAY_FADE_ALPHA: u8 = 6;
+const SPRAY_DOT_RADIUS: f32 = 1.5;
+const SPRAY_DOT_ALPHA: u8 = 90;
+// Frames the last generation takes to fade out while the next one fades in
+const RESPAWN_FRAMES: u32 = 30;
+// Live rockets are tinted towards this the further through their genome they are
+const AGE_TINT_COLOR: Color = Color::RAYWHITE;
+const AGE_TINT_MAX: f32 = 0.6;
+const GHOST_PATH_COLOR: Color = Color::new(230, 230, 230, 50);
+// The newest frozen path is drawn fully opaque, the oldest one fades down to this
+const FROZEN_PATH_COLOR: Color = Color::new(90, 255, 200, 255);
+const FROZEN_PATH_MIN_ALPHA: u8 = 40;
+const LEADER_RING_COLOR: Color = Color::GOLD;
+const LEADER_RING_RADIUS: f32 = 32.0;
+const VICTORY_FLASH_COLOR: Color = Color::new(255, 245, 160, 255);
+const VICTORY_PAUSE_SECS: f32 = 2.0;
+// Times per second the winning rocket blinks during the victory pause
+const VICTORY_FLASH_RATE: f32 = 6.0;
+const SOLVED_BANNER_TEXT_SIZE: i32 = 30;
 
 const TARGET_OUTER_COLOR: Color = Color::RAYWHITE;
 const TARGET_INNER_COLOR: Color = Color::new(199, 111, 40, 255);
 const TARGET_RADIUS: f32 = 30.0;
+const REACHED_TARGET_ALPHA: u8 = 70;
+// A ring keeps spreading out from the edge of the success zone and fading as it goes
+const TARGET_PULSE_RATE: f32 = 0.8;
+const TARGET_PULSE_SPREAD: f32 = 12.0;
+const TARGET_PULSE_ALPHA: u8 = 120;
 
 const WALL_SIZE: Vector2 = Vector2::new(200.0, 20.0);
-const WALL_COUNT: usize = 2;
 const WALL_COLOR: Color = Color::new(171, 171, 171, 255);
+const WALL_PREVIEW_COLOR: Color = Color::new(171, 171, 171, 120);
+const WALL_GRID_CELL_SIZE: f32 = 100.0;
+const NAV_GRID_CELL_SIZE: f32 = 20.0;
+// Holding shift while dragging a wall snaps its corner to this grid
+const WALL_SNAP_SIZE: f32 = 20.0;
+const GATE_CLOSED_COLOR: Color = Color::new(200, 50, 50, 255);
+const GATE_OPEN_COLOR: Color = Color::new(60, 180, 75, 120);
 
 const TIMER_RECT_COLOR: Color = Color::LIME;
 const TIMER_RECT_HEIGHT: i32 = 15;
+// Space between the target and the timer ring drawn around it, and the ring's thickness
+const TIMER_RING_GAP: f32 = 6.0;
+const TIMER_RING_WIDTH: f32 = 5.0;
+
+const DIST_LINE_NEAR_COLOR: Color = Color::new(60, 200, 80, 90);
+const DIST_LINE_FAR_COLOR: Color = Color::new(220, 60, 50, 90);
+
+const MINIMAP_WIDTH: f32 = 200.0;
+const MINIMAP_MARGIN: f32 = 15.0;
+const MINIMAP_VIEWPORT_COLOR: Color = Color::YELLOW;
+
+const DEATH_CROSS_SIZE: f32 = 4.0;
+const DEATH_COLOR: Color = Color::new(230, 60, 50, 200);
+const DEATH_CELL_SIZE: f32 = 20.0;
+// Alpha of the density map cell with the most deaths in it
+const DEATH_DENSITY_MAX_ALPHA: f32 = 160.0;
+
+const LANDSCAPE_CELL_SIZE: f32 = 20.0;
+const LANDSCAPE_NEAR_COLOR: Color = Color::new(60, 200, 80, 70);
+const LANDSCAPE_FAR_COLOR: Color = Color::new(40, 70, 160, 70);
+
+const HEATMAP_HEIGHT: i32 = 5;
+const HEATMAP_LOW_COLOR: Color = Color::new(40, 70, 160, 255);
+const HEATMAP_HIGH_COLOR: Color = Color::new(230, 60, 50, 255);
+
+// Parameter grid tried by `--sweep`
+const SWEEP_MUTATION_RATES: [f32; 4] = [0.01, 0.03, 0.05, 0.1];
+const SWEEP_ROCKET_COUNTS: [usize; 3] = [40, 80, 160];
+// Seed every sweep and benchmark run starts from when `--seed` isn't given
+const SWEEP_DEFAULT_SEED: u64 = 0;
+
+const MAX_SIM_SPEED: u32 = 64;
+// The simulation ticks at 60 Hz whatever the display refreshes at
+const SIM_TICK: f32 = 1.0 / 60.0;
+// Ticks caught up on in one rendered frame at most, so a long stall doesn't snowball
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+const PANEL_WIDTH: f32 = 260.0;
+const PANEL_ROW_HEIGHT: f32 = 26.0;
+const PANEL_BUTTON_SIZE: f32 = 20.0;
+const PANEL_BUTTON_COLOR: Color = Color::new(70, 70, 70, 255);
+
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+const ZOOM_STEP: f32 = 0.1;
+
+const LEADERBOARD_LEN: usize = 5;
+
+const TOOLTIP_GENE_COUNT: usize = 5;
+const TOOLTIP_TEXT_SIZE: i32 = 10;
+const TOOLTIP_LINE_HEIGHT: i32 = 12;
+
+const HUD_TEXT_SIZE: i32 = 20;
+const HUD_LINE_HEIGHT: i32 = 22;
+const WIND_ARROW_LENGTH: f32 = 30.0;
+const WIND_ARROW_HEAD: f32 = 10.0;
+
+const GRAPH_SIZE: Vector2 = Vector2::new(260.0, 130.0);
+const GRAPH_MARGIN: f32 = 15.0;
+const GRAPH_BACKGROUND_COLOR: Color = Color::new(40, 40, 40, 200);
+const GRAPH_LINE_COLOR: Color = Color::LIME;
+const GRAPH_TEXT_SIZE: i32 = 10;
 
 // ================================== UTIL functions
-fn rand_f32(min: f32, max: f32) -> f32 {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(min..max) as f32
+fn rand_f32(rng: &mut impl Rng, min: f32, max: f32) -> f32 {
+    rng.gen_range(min..max)
+}
+
+// Box-Muller transform, centered on 0
+fn rand_gaussian(rng: &mut impl Rng, std: f32) -> f32 {
+    let u1 = rng.gen::<f32>().max(f32::MIN_POSITIVE);
+    let u2 = rng.gen::<f32>();
+    std * (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+    Color::new(
+        lerp(a.r, b.r),
+        lerp(a.g, b.g),
+        lerp(a.b, b.b),
+        lerp(a.a, b.a),
+    )
+}
+
+fn rand_force(rng: &mut impl Rng, max_force: f32) -> Vector2 {
+    let angle = rand_f32(rng, 0.0, std::f32::consts::TAU);
+    let magnitude = rand_f32(rng, 0.0, max_force);
+    Vector2::new(magnitude * angle.cos(), magnitude * angle.sin())
+}
+
+// Takes gene `i` from `a` wherever `from_a[i]` is set, otherwise from `b`. Genomes line up
+// from their first gene, whatever the longer one has past the end of `from_a` comes from
+// `a` or `b` by `tail_from_a`
+fn mix_genes<T: Copy>(a: &[T], b: &[T], from_a: &[bool], tail_from_a: bool) -> Vec<T> {
+    let tail = if tail_from_a { a } else { b };
+    from_a
+        .iter()
+        .enumerate()
+        .map(|(i, &take_a)| if take_a { a[i] } else { b[i] })
+        .chain(tail.iter().skip(from_a.len()).copied())
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum GeneEncoding {
+    // Each gene turns the rocket by some degrees
+    Angle,
+    // Each gene is a force added straight to the acceleration
+    Force,
+}
+
+// How the first generation's angle genes are picked, force genes are always uniform
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InitDistribution {
+    // Anywhere in `[-degree_change, degree_change]`
+    Uniform,
+    // Mostly small turns so rockets start out flying close to straight,
+    // still clamped to `degree_change`
+    Gaussian { std: f32 },
+}
+
+// Largest turn an angle gene can hold, by where the gene sits in the genome
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DegreeSchedule {
+    // `degree_change` for every gene
+    Flat,
+    // Eases from `start` degrees at the first gene to `end` at the last one
+    Linear { start: f32, end: f32 },
+}
+
+// How many rockets fly in each generation
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum PopulationSchedule {
+    // `rocket_count` every generation
+    Constant,
+    // Eases from `start` rockets in the first generation to `end` after `generations` of them
+    Linear {
+        start: usize,
+        end: usize,
+        generations: u32,
+    },
+}
+
+// Untagged so genomes saved before force genes existed still load as angles
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Genes {
+    Angle(Vec<f32>),
+    Force(#[serde(with = "vector2_vec")] Vec<Vector2>),
+}
+
+impl Genes {
+    fn len(&self) -> usize {
+        match self {
+            Genes::Angle(genes) => genes.len(),
+            Genes::Force(genes) => genes.len(),
+        }
+    }
+
+    fn encoding(&self) -> GeneEncoding {
+        match self {
+            Genes::Angle(_) => GeneEncoding::Angle,
+            Genes::Force(_) => GeneEncoding::Force,
+        }
+    }
+
+    // Angle genes are put on the x axis so both encodings can be compared as vectors
+    fn as_vector(&self, ind: usize) -> Vector2 {
+        match self {
+            Genes::Angle(genes) => Vector2::new(genes[ind], 0.0),
+            Genes::Force(genes) => genes[ind],
+        }
+    }
+
+    fn remove(&mut self, ind: usize) {
+        match self {
+            Genes::Angle(genes) => {
+                genes.remove(ind);
+            }
+            Genes::Force(genes) => {
+                genes.remove(ind);
+            }
+        }
+    }
+
+    // Padding flies straight or coasts, depending on the encoding
+    fn resize(&mut self, len: usize) {
+        match self {
+            Genes::Angle(genes) => genes.resize(len, 0.0),
+            Genes::Force(genes) => genes.resize(len, Vector2::zero()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Gene {
+    Angle(f32),
+    Force(Vector2),
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct DNA {
-    pub genes: [f32; GENE_LEN],
+    pub genes: Genes,
+    // Defaulted so genome files from before worlds could be saved still load
+    #[serde(default)]
     pub curr_gene: usize,
     pub fitness: f32,
+    // Hue in degrees handed down from the fitter parent, so one ancestor's line shares a color
+    #[serde(default)]
+    pub lineage_hue: f32,
 }
 
 impl DNA {
-    fn new() -> Self {
+    fn new(gene_len: usize, encoding: GeneEncoding) -> Self {
+        let genes = match encoding {
+            GeneEncoding::Angle => Genes::Angle(vec![0.0; gene_len]),
+            GeneEncoding::Force => Genes::Force(vec![Vector2::zero(); gene_len]),
+        };
         Self {
-            genes: [0.0; GENE_LEN],
+            genes,
             curr_gene: 0,
             fitness: 0.0,
+            lineage_hue: 0.0,
         }
     }
 
-    fn randomize(&mut self) {
-        for el in &mut self.genes {
-            *el = rand_f32(-DEGREE_CHANGE, DEGREE_CHANGE);
+    fn lineage_color(&self) -> Color {
+        Color::color_from_hsv(self.lineage_hue, 0.6, 0.95)
+    }
+
+    // `degree_change` gives the largest turn for the gene at each index
+    fn randomize(
+        &mut self,
+        distribution: InitDistribution,
+        degree_change: impl Fn(usize) -> f32,
+        max_force: f32,
+        rng: &mut impl Rng,
+    ) {
+        self.lineage_hue = rand_f32(rng, 0.0, 360.0);
+        match &mut self.genes {
+            Genes::Angle(genes) => {
+                for (ind, el) in genes.iter_mut().enumerate() {
+                    let degree_change = degree_change(ind);
+                    *el = match distribution {
+                        InitDistribution::Uniform => rand_f32(rng, -degree_change, degree_change),
+                        InitDistribution::Gaussian { std } => {
+                            rand_gaussian(rng, std).clamp(-degree_change, degree_change)
+                        }
+                    };
+                }
+            }
+            Genes::Force(genes) => {
+                for el in genes {
+                    *el = rand_force(rng, max_force);
+                }
+            }
         }
     }
 
-    fn next_angle(&mut self) -> f32 {
-        if self.curr_gene >= GENE_LEN {
-            return self.genes[GENE_LEN - 1];
+    // Cuts the genome down to `len` genes, or tops it up with random ones picked like a
+    // first generation's
+    fn fit_to_len(
+        &mut self,
+        len: usize,
+        distribution: InitDistribution,
+        degree_change: impl Fn(usize) -> f32,
+        max_force: f32,
+        rng: &mut impl Rng,
+    ) {
+        let old_len = self.genes.len();
+        if len <= old_len {
+            self.genes.resize(len);
+            return;
+        }
+        let mut extra = DNA::new(len - old_len, self.genes.encoding());
+        extra.randomize(
+            distribution,
+            |ind| degree_change(old_len + ind),
+            max_force,
+            rng,
+        );
+        match (&mut self.genes, extra.genes) {
+            (Genes::Angle(genes), Genes::Angle(tail)) => genes.extend(tail),
+            (Genes::Force(genes), Genes::Force(tail)) => genes.extend(tail),
+            _ => unreachable!("extra genes share the genome's encoding"),
         }
-        let next_angle = self.genes[self.curr_gene];
-        self.curr_gene += 1;
-        next_angle
     }
 
-    fn crossover(parent_a: &Self, parent_b: &Self) -> Self {
-        let mut rng = rand::thread_rng();
-        let rand_split_point = rng.gen_range(0..GENE_LEN);
-        let mut child = Self::new();
-        for i in 0..GENE_LEN {
-            if i < rand_split_point {
-                child.genes[i] = parent_a.genes[i];
-            } else {
-                child.genes[i] = parent_b.genes[i];
+    // None once every gene has been used
+    fn next_gene(&mut self) -> Option<Gene> {
+        let gene = match &self.genes {
+            Genes::Angle(genes) => genes.get(self.curr_gene).copied().map(Gene::Angle),
+            Genes::Force(genes) => genes.get(self.curr_gene).copied().map(Gene::Force),
+        };
+        if gene.is_some() {
+            self.curr_gene += 1;
+        }
+        gene
+    }
+
+    fn crossover(
+        kind: CrossoverKind,
+        parent_a: &Self,
+        parent_b: &Self,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let gene_len = parent_a.genes.len().min(parent_b.genes.len());
+        match kind {
+            CrossoverKind::OnePoint => {
+                let rand_split_point = rng.gen_range(0..gene_len);
+                Self::crossover_at(parent_a, parent_b, rand_split_point)
+            }
+            CrossoverKind::TwoPoint => {
+                let point_a = rng.gen_range(0..gene_len);
+                let point_b = rng.gen_range(0..gene_len);
+                let segment = point_a.min(point_b)..point_a.max(point_b);
+                let from_a: Vec<bool> = (0..gene_len).map(|i| !segment.contains(&i)).collect();
+                Self::from_parents(parent_a, parent_b, &from_a, true)
+            }
+            CrossoverKind::Uniform => {
+                let from_a: Vec<bool> = (0..gene_len).map(|_| rng.gen_bool(0.5)).collect();
+                // Only genomes of different lengths draw for the tail, so fixed length runs
+                // stay the same as before genomes could differ
+                let tail_from_a = parent_a.genes.len() != parent_b.genes.len() && rng.gen_bool(0.5);
+                Self::from_parents(parent_a, parent_b, &from_a, tail_from_a)
+            }
+        }
+    }
+
+    // One-point crossover: genes before `split` come from parent a, the rest from parent b,
+    // so a split of 0 copies parent b entirely
+    fn crossover_at(parent_a: &Self, parent_b: &Self, split: usize) -> Self {
+        let gene_len = parent_a.genes.len().min(parent_b.genes.len());
+        let from_a: Vec<bool> = (0..gene_len).map(|i| i < split).collect();
+        Self::from_parents(parent_a, parent_b, &from_a, false)
+    }
+
+    // Child that takes every gene from a single parent
+    fn clone_of(parent: &Self) -> Self {
+        Self {
+            genes: parent.genes.clone(),
+            curr_gene: 0,
+            fitness: 0.0,
+            lineage_hue: parent.lineage_hue,
+        }
+    }
+
+    fn from_parents(parent_a: &Self, parent_b: &Self, from_a: &[bool], tail_from_a: bool) -> Self {
+        let genes = match (&parent_a.genes, &parent_b.genes) {
+            (Genes::Angle(a), Genes::Angle(b)) => {
+                Genes::Angle(mix_genes(a, b, from_a, tail_from_a))
             }
+            (Genes::Force(a), Genes::Force(b)) => {
+                Genes::Force(mix_genes(a, b, from_a, tail_from_a))
+            }
+            _ => unreachable!("every rocket in a world shares the same gene encoding"),
+        };
+        let fitter = if parent_a.fitness >= parent_b.fitness {
+            parent_a
+        } else {
+            parent_b
+        };
+        Self {
+            genes,
+            curr_gene: 0,
+            fitness: 0.0,
+            lineage_hue: fitter.lineage_hue,
+        }
+    }
+
+    fn mutate(
+        dna: &mut DNA,
+        kind: MutationKind,
+        mutation_rate: f32,
+        degree_change: impl Fn(usize) -> f32,
+        max_force: f32,
+        rng: &mut impl Rng,
+    ) {
+        let mut mutated = false;
+        match &mut dna.genes {
+            Genes::Angle(genes) => {
+                for (ind, gene) in genes.iter_mut().enumerate() {
+                    if rng.gen::<f32>() < mutation_rate {
+                        mutated = true;
+                        let degree_change = degree_change(ind);
+                        *gene = match kind {
+                            MutationKind::Replace => rand_f32(rng, -degree_change, degree_change),
+                            MutationKind::Gaussian { std } => (*gene + rand_gaussian(rng, std))
+                                .clamp(-degree_change, degree_change),
+                        };
+                    }
+                }
+            }
+            Genes::Force(genes) => {
+                for gene in genes {
+                    if rng.gen::<f32>() < mutation_rate {
+                        mutated = true;
+                        *gene = match kind {
+                            MutationKind::Replace => rand_force(rng, max_force),
+                            MutationKind::Gaussian { std } => {
+                                let offset =
+                                    Vector2::new(rand_gaussian(rng, std), rand_gaussian(rng, std));
+                                let gene = *gene + offset;
+                                let magnitude = gene.length();
+                                if magnitude > max_force {
+                                    gene * (max_force / magnitude)
+                                } else {
+                                    gene
+                                }
+                            }
+                        };
+                    }
+                }
+            }
+        }
+        // Mutants drift a little away from their line's hue
+        if mutated {
+            dna.lineage_hue =
+                (dna.lineage_hue + rand_gaussian(rng, LINEAGE_HUE_DRIFT)).rem_euclid(360.0);
         }
-        child
     }
 
-    fn mutate(dna: &mut DNA) {
-        for i in 0..GENE_LEN {
-            let rand_num = rand::random::<f32>();
-            if rand_num < MUTATION_RATE {
-                dna.genes[i] = rand_f32(-DEGREE_CHANGE, DEGREE_CHANGE);
+    // Maybe inserts a fresh random gene and maybe deletes one, each at a random spot,
+    // without leaving `min_len..=max_len`
+    fn mutate_length(
+        dna: &mut DNA,
+        length: LengthMutation,
+        degree_change: impl Fn(usize) -> f32,
+        max_force: f32,
+        rng: &mut impl Rng,
+    ) {
+        if rng.gen::<f32>() < length.rate && dna.genes.len() < length.max_len {
+            let ind = rng.gen_range(0..=dna.genes.len());
+            match &mut dna.genes {
+                Genes::Angle(genes) => {
+                    let degree_change = degree_change(ind);
+                    genes.insert(ind, rand_f32(rng, -degree_change, degree_change));
+                }
+                Genes::Force(genes) => genes.insert(ind, rand_force(rng, max_force)),
             }
         }
+        if rng.gen::<f32>() < length.rate && dna.genes.len() > length.min_len {
+            let ind = rng.gen_range(0..dna.genes.len());
+            dna.genes.remove(ind);
+        }
     }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum MutationKind {
+    // Swaps the gene for a fresh random one
+    Replace,
+    // Nudges the gene by a normal offset, kept inside the range a fresh gene could take
+    Gaussian { std: f32 },
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CrossoverKind {
+    // Genes before a random split come from parent a, the rest from parent b
+    OnePoint,
+    // A random middle segment comes from parent b, the rest from parent a
+    TwoPoint,
+    // Every gene comes from a randomly picked parent
+    Uniform,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MotionModel {
+    // Genes turn the rocket, which always flies at `rocket_speed`
+    ConstantSpeed,
+    // Genes steer a thrust that accelerates the rocket up to `rocket_speed`
+    Thrust,
+}
+
+// What happens to a rocket that flies off the screen
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BoundaryMode {
+    Kill,
+    // Comes back in from the opposite edge
+    Wrap,
+    // Bounces off the edge
+    Reflect,
+}
+
+// What has to leave the screen for `BoundaryMode::Kill` to kill a rocket
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BoundaryCheck {
+    Center,
+    // Any part of the rotated body
+    Body,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SelectionStrategy {
+    Roulette,
+    Tournament { k: usize },
+    // Like roulette, but weighted by fitness rank instead of the fitness itself
+    Rank,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum FitnessKind {
+    // Original formula: 1 - dist / sum of all distances
+    Relative,
+    // 1 / (dist + 1), normalized so the best rocket has fitness 1
+    Inverse,
+    // (max_dist - dist)^2, normalized so the best rocket has fitness 1
+    Squared,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+enum Axis {
+    X,
+    Y,
+}
+
+// Movement of the final target, computed from the frame counter so seeded runs stay reproducible
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum TargetMotion {
+    Static,
+    Circle {
+        #[serde(with = "Vector2Def")]
+        center: Vector2,
+        radius: f32,
+        // Radians per frame
+        speed: f32,
+    },
+    // Swings around the configured target position
+    Oscillate {
+        axis: Axis,
+        amplitude: f32,
+        // Radians per frame
+        speed: f32,
+    },
+}
+
+// Where the final target moves each time the map is solved, for a never ending curriculum
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DifficultyRamp {
+    // Visited in order, the ramp ends on the last one
+    Targets {
+        #[serde(with = "vector2_vec")]
+        targets: Vec<Vector2>,
+    },
+    // Pushes the target this many pixels further away from the spawn, until the edge of the
+    // screen stops it
+    Distance {
+        step: f32,
+    },
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 enum RocketState {
     Alive,
     Dead,
     Successful,
+    // Still flying when the generation ran out of frames
+    Timeout,
+}
+
+// Wall that is only solid for part of every period, driven by the frame counter so
+// seeded runs stay reproducible
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct Gate {
+    #[serde(with = "RectangleDef")]
+    pub rect: Rectangle,
+    // Frames per closed + open cycle, every cycle starts closed
+    pub period: u32,
+    // Share of the period the gate is open for
+    pub open_fraction: f32,
+}
+
+impl Gate {
+    fn is_open(&self, frame: u32) -> bool {
+        let closed_frames = (self.period as f32 * (1.0 - self.open_fraction)) as u32;
+        frame % self.period >= closed_frames
+    }
+}
+
+// Once the best fitness has gone `generations` generations without improving, `fraction`
+// of the next generation's children are swapped for random rockets
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct Catastrophe {
+    pub generations: u32,
+    pub fraction: f32,
+}
+
+// Lets genomes grow and shrink over the generations instead of all having `gene_len` genes
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+struct LengthMutation {
+    // Chance a child gets a gene inserted, and separately that it gets one deleted
+    pub rate: f32,
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+// How much each end state scales a rocket's share of the roulette mating pool
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct StateWeights {
+    pub dead: f32,
+    // Rockets that were still flying at the end used to count as alive
+    #[serde(alias = "alive")]
+    pub timeout: f32,
+    pub successful: f32,
+}
+
+impl Default for StateWeights {
+    fn default() -> Self {
+        Self {
+            dead: 0.6,
+            timeout: 1.0,
+            successful: 2.0,
+        }
+    }
+}
+
+// Every genome of one generation along with the map it flew on, physics come from the config
+#[derive(Clone, Serialize, Deserialize)]
+struct SavedGeneration {
+    pub generation: u32,
+    pub genomes: Vec<Genes>,
+    #[serde(with = "vector2_vec")]
+    pub targets: Vec<Vector2>,
+    // Generations saved before the radius was configurable keep the config's
+    #[serde(default)]
+    pub target_radius: Option<f32>,
+    #[serde(with = "rectangle_vec")]
+    pub walls: Vec<Rectangle>,
+    #[serde(default, with = "polygon_vec")]
+    pub polygon_walls: Vec<Vec<Vector2>>,
+    #[serde(with = "circle_vec")]
+    pub circle_obstacles: Vec<(Vector2, f32)>,
+    pub gates: Vec<Gate>,
+}
+
+// Bonuses and penalties on top of the distance based part of a rocket's fitness
+fn adjusted_fitness(fitness: f32, rocket: &Rocket, config: &SimConfig) -> f32 {
+    // Every waypoint passed is worth more than getting closer to the next one
+    let mut fitness = fitness + rocket.next_target as f32;
+    // Reward rockets that reached the target sooner
+    if let Some(frames) = rocket.frames_to_success {
+        let gene_len = config.max_gene_len() as u32;
+        fitness *= gene_len.saturating_sub(frames) as f32 / gene_len as f32;
+        // and, with genomes that can differ in length, ones that got there with fewer genes
+        if config.length_mutation.is_some() {
+            let spare_genes = gene_len.saturating_sub(rocket.dna.genes.len() as u32);
+            fitness *= 1.0 + spare_genes as f32 / gene_len as f32;
+        }
+    }
+    // Prefer straighter routes
+    (fitness - config.path_length_penalty * rocket.path_length).max(0.0)
 }
 
+// What a rocket ran into this frame
 #[derive(Clone, Copy)]
+enum Collision {
+    // Anything that kills on contact
+    Obstacle,
+    // Walls only do damage, along with the move that pushes the rocket back out
+    Wall(Vector2),
+    Target,
+}
+
+// Shortest move along one axis that takes `body` out of `wall`
+fn push_out(body: &Rectangle, wall: &Rectangle) -> Vector2 {
+    let left = body.x + body.width - wall.x;
+    let right = wall.x + wall.width - body.x;
+    let up = body.y + body.height - wall.y;
+    let down = wall.y + wall.height - body.y;
+    let min = left.min(right).min(up).min(down);
+    if min == left {
+        Vector2::new(-left, 0.0)
+    } else if min == right {
+        Vector2::new(right, 0.0)
+    } else if min == up {
+        Vector2::new(0.0, -up)
+    } else {
+        Vector2::new(0.0, down)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct Rocket {
     pub dna: DNA,
+    #[serde(with = "Vector2Def")]
     pub pos: Vector2,
     pub state: RocketState,
     pub angle: f32,
+    #[serde(with = "Vector2Def")]
+    pub velocity: Vector2,
+    #[serde(with = "Vector2Def")]
+    pub acceleration: Vector2,
     pub dist_from_target: f32,
+    pub frames_to_success: Option<u32>,
+    // Every position the rocket has flown through this generation
+    #[serde(with = "vector2_vec")]
+    pub path: Vec<Vector2>,
+    // Distance flown this generation
+    pub path_length: f32,
+    // Frames of flight left, the rocket dies once it runs out
+    pub fuel: u32,
+    // Taken down by wall hits, the rocket dies once it reaches zero
+    pub health: f32,
+    pub next_target: usize,
+    // Degrees turned per frame, only carried between frames with `angular_momentum`
+    pub angular_velocity: f32,
+    // Where the rocket was when it died, if it has
+    #[serde(with = "vector2_opt")]
+    pub death_pos: Option<Vector2>,
 }
 
 impl Rocket {
-    fn new(pos: Vector2) -> Self {
+    fn new(
+        pos: Vector2,
+        angle: f32,
+        gene_len: usize,
+        encoding: GeneEncoding,
+        fuel: u32,
+        health: f32,
+    ) -> Self {
         Self {
-            dna: DNA::new(),
+            dna: DNA::new(gene_len, encoding),
             pos,
-            angle: -90.0,
+            angle,
+            velocity: Vector2::zero(),
+            acceleration: Vector2::zero(),
             state: RocketState::Alive,
             dist_from_target: 0.0,
+            frames_to_success: None,
+            path: vec![],
+            path_length: 0.0,
+            fuel,
+            health,
+            next_target: 0,
+            angular_velocity: 0.0,
+            death_pos: None,
         }
     }
 
-    fn calc_offset(&self) -> Vector2 {
+    fn crash(&mut self) {
+        self.state = RocketState::Dead;
+        self.death_pos = Some(self.pos);
+    }
+
+    // Axis-aligned box around the rotated body as drawn in `render`
+    fn bounding_box(&self) -> Rectangle {
+        let body_angle = (self.angle + 90.0).to_radians();
+        let (sin, cos) = (body_angle.sin().abs(), body_angle.cos().abs());
+        let half_width = (ROCKET_SIZE.x * cos + ROCKET_SIZE.y * sin) / 2.0;
+        let half_height = (ROCKET_SIZE.x * sin + ROCKET_SIZE.y * cos) / 2.0;
+        Rectangle::new(
+            self.pos.x - half_width,
+            self.pos.y - half_height,
+            half_width * 2.0,
+            half_height * 2.0,
+        )
+    }
+
+    fn calc_offset(&self, speed: f32) -> Vector2 {
         Vector2::new(
-            ROCKET_SPEED * self.angle.to_radians().cos(),
-            ROCKET_SPEED * self.angle.to_radians().sin(),
+            speed * self.angle.to_radians().cos(),
+            speed * self.angle.to_radians().sin(),
         )
     }
+
+    // Pushes the rocket out of a wall and mirrors its heading off the face it hit
+    fn bounce(&mut self, push: Vector2) {
+        self.pos += push;
+        if push.y == 0.0 {
+            self.velocity.x = -self.velocity.x;
+            self.angle = 180.0 - self.angle;
+        } else if push.x == 0.0 {
+            self.velocity.y = -self.velocity.y;
+            self.angle = -self.angle;
+        } else {
+            // Slanted polygon edges reflect the heading across the edge's normal
+            let normal = push.normalized();
+            self.velocity -= normal * 2.0 * self.velocity.dot(normal);
+            let heading = self.angle.to_radians();
+            let mut dir = Vector2::new(heading.cos(), heading.sin());
+            dir -= normal * 2.0 * dir.dot(normal);
+            self.angle = dir.y.atan2(dir.x).to_degrees();
+        }
+    }
+
+    // Keeps the rocket on a `width` x `height` screen, `Kill` is handled as a collision instead
+    fn apply_boundary(&mut self, mode: BoundaryMode, width: f32, height: f32) {
+        match mode {
+            BoundaryMode::Kill => {}
+            BoundaryMode::Wrap => {
+                self.pos.x = self.pos.x.rem_euclid(width);
+                self.pos.y = self.pos.y.rem_euclid(height);
+            }
+            BoundaryMode::Reflect => {
+                if self.pos.x < 0.0 || self.pos.x > width {
+                    self.pos.x = self.pos.x.clamp(0.0, width);
+                    self.velocity.x = -self.velocity.x;
+                    self.angle = 180.0 - self.angle;
+                }
+                if self.pos.y < 0.0 || self.pos.y > height {
+                    self.pos.y = self.pos.y.clamp(0.0, height);
+                    self.velocity.y = -self.velocity.y;
+                    self.angle = -self.angle;
+                }
+            }
+        }
+    }
+
+    // Turn to make this frame for an angle gene, never more than `max_turn_rate` degrees
+    // With `momentum` the gene nudges a spin the rocket keeps, instead of turning it directly
+    fn turn_rate(&mut self, gene: f32, max_turn_rate: f32, momentum: bool) -> f32 {
+        let turn = if momentum {
+            self.angular_velocity + gene
+        } else {
+            gene
+        };
+        self.angular_velocity = turn.clamp(-max_turn_rate, max_turn_rate);
+        self.angular_velocity
+    }
+
+    // Thrust is pointed `turn` degrees off the current heading
+    fn thrust_force(&self, turn: f32, thrust: f32) -> Vector2 {
+        let thrust_angle = (self.angle + turn).to_radians();
+        Vector2::new(thrust * thrust_angle.cos(), thrust * thrust_angle.sin())
+    }
+
+    // Euler step, the heading then follows the velocity so the body is drawn facing where it's going
+    fn apply_force(&mut self, force: Vector2, max_speed: f32) -> Vector2 {
+        self.acceleration = force;
+        self.velocity += self.acceleration;
+
+        let speed = self.velocity.length();
+        if speed > max_speed {
+            self.velocity *= max_speed / speed;
+        }
+        if speed > 0.0 {
+            self.angle = self.velocity.y.atan2(self.velocity.x).to_degrees();
+        }
+        self.velocity
+    }
 }
 
+// Everything derived from other fields is left out of saves and rebuilt by `restore`
+#[derive(Serialize, Deserialize)]
 struct World {
-    pub rockets: [Rocket; ROCKET_COUNT],
+    pub config: SimConfig,
+    pub rockets: Vec<Rocket>,
+    // Rockets still flying, reaching the final target or crashing both take one off
     pub alive_count: i32,
-    pub walls: [Rectangle; WALL_COUNT],
-    pub target: Vector2,
+    #[serde(with = "rectangle_vec")]
+    pub walls: Vec<Rectangle>,
+    // Built from `walls`, so it has to be rebuilt whenever they change
+    #[serde(skip)]
+    wall_grid: SpatialGrid,
+    #[serde(with = "polygon_vec")]
+    pub polygon_walls: Vec<Vec<Vector2>>,
+    #[serde(with = "circle_vec")]
+    pub circle_obstacles: Vec<(Vector2, f32)>,
+    pub gates: Vec<Gate>,
+    #[serde(with = "vector2_vec")]
+    pub targets: Vec<Vector2>,
+    pub target_motion: TargetMotion,
+    pub boundary_mode: BoundaryMode,
+    // Where new rockets start and the heading they start with, in degrees
+    #[serde(with = "Vector2Def")]
+    pub spawn: Vector2,
+    pub spawn_angle: f32,
     pub frame_counter: u32,
+    #[serde(with = "RectangleDef")]
     pub timer_rect: Rectangle,
     pub generation: u32,
+    // Number of updates run per rendered frame
+    pub sim_speed: u32,
+    // Frames simulated since the start, across every generation
+    pub sim_frames: u64,
+    // Wall clock seconds spent unpaused since the start
+    pub run_time: f32,
+    pub selection_strategy: SelectionStrategy,
+    // Alive rockets are drawn in this, so racing populations can be told apart
+    #[serde(with = "ColorDef")]
+    pub species_color: Color,
+    pub best_fitness_history: Vec<f32>,
+    pub fastest_success: Option<u32>,
+    // Fade between generations, only wanted when a person is watching at normal speed
+    pub animate_respawn: bool,
+    // Frames left of the respawn fade, `update` holds off on flying until it runs out
+    pub respawn_frames_left: u32,
+    // Last generation's rockets, only kept around to be drawn fading out
+    #[serde(skip)]
+    pub fading_rockets: Vec<Rocket>,
+    // Rocket that reached the final target first in the current generation
+    pub first_success: Option<usize>,
+    // First generation where a rocket reached the final target
+    pub first_solved_generation: Option<u32>,
+    // First generation where at least `solved_success_rate` of the rockets reached it
+    pub solved_generation: Option<u32>,
+    // Generation of every solve that moved the target on with `difficulty_ramp`, so its
+    // length is the current difficulty level
+    pub ramp_solves: Vec<u32>,
+    // Generations since the best fitness last beat `best_fitness_ever`
+    pub stagnant_generations: u32,
+    pub best_fitness_ever: f32,
+    // Successful rockets over every finished generation
+    pub total_successes: usize,
+    // Fittest genome of the last finished generation
+    pub best_dna: Option<DNA>,
+    // Path of the best rocket from the previous generation
+    #[serde(with = "vector2_vec")]
+    pub ghost_path: Vec<Vector2>,
+    // Spread of each gene across the previous generation, low means it has converged
+    pub gene_variance: Vec<f32>,
+    // Average distance between two genomes of the previous generation
+    pub diversity: Option<f32>,
+    // Loaded genome that gets planted into the next generation
+    pub seed_dna: Option<DNA>,
+    // Loaded generation that gets flown again instead of evolving
+    pub replay: Option<SavedGeneration>,
+    // Where rockets died in each of the last `DEATH_HISTORY_LEN` finished generations, oldest first
+    #[serde(skip)]
+    pub death_history: VecDeque<Vec<Vector2>>,
+    // Flight of the fastest successful rocket of each of the last `FROZEN_PATHS_LEN`
+    // generations that had one, with its generation, oldest first
+    #[serde(skip)]
+    pub frozen_paths: VecDeque<(u32, Vec<Vector2>)>,
+    // Starting genomes of the last `HISTORY_LEN` generations, oldest first
+    pub history: VecDeque<(u32, Vec<Genes>)>,
+    // Snapshot in `history` that is currently flying
+    pub history_pos: usize,
+    // Set from the parameter panel, only take effect once the next generation starts
+    pub pending_gene_len: Option<usize>,
+    pub pending_rocket_count: Option<usize>,
+    #[serde(skip)]
+    pub stats_writer: Option<StatsWriter>,
+    // Only kept while a benchmark is running
+    #[serde(skip)]
+    pub phase_times: Option<PhaseTimes>,
+    // Distance from the center of every `LANDSCAPE_CELL_SIZE` cell to the final target, row by row
+    // and scaled to [0, 1], rebuilt only once that target has moved
+    #[serde(skip)]
+    pub landscape: Vec<f32>,
+    #[serde(skip)]
+    landscape_target: Option<Vector2>,
+    // One path distance field per target with `path_distance`, built from `walls` and the
+    // targets, so it's dropped whenever the walls change
+    #[serde(skip)]
+    nav_grids: Vec<NavGrid>,
     mating_pool: Vec<usize>,
+    // Drawn once from the starting rng, every child's rng is derived from it by `child_rng`
+    // and this world's own rng by `generation_rng`
+    run_seed: u64,
+    // Started over from `generation_rng` by every restart, so a save doesn't need its state
+    // and saving never changes what the run draws next
+    #[serde(skip, default = "StdRng::from_entropy")]
+    rng: StdRng,
 }
 
 impl World {
-    fn new() -> Self {
+    fn new(config: SimConfig, mut rng: StdRng) -> Self {
+        let (screen_width, screen_height) = (config.screen_width, config.screen_height);
+        let run_seed = rng.gen();
         let mut instance = Self {
-            rockets: [Rocket::new(Vector2::new(
-                (SCREEN_WIDTH / 2) as f32,
-                (SCREEN_HEIGHT - 75) as f32,
-            )); ROCKET_COUNT],
-            alive_count: ROCKET_COUNT as i32,
-            walls: [
-                Rectangle::new(300.0, 250.0, WALL_SIZE.x, WALL_SIZE.y),
-                Rectangle::new(150.0, 300.0, WALL_SIZE.x, WALL_SIZE.y),
-            ],
-            target: Vector2::new(100.0, 100.0),
+            rockets: vec![],
+            alive_count: 0,
+            walls: config.walls.clone(),
+            wall_grid: SpatialGrid::new(
+                &config.walls,
+                screen_width as f32,
+                screen_height as f32,
+                WALL_GRID_CELL_SIZE,
+            ),
+            polygon_walls: config.polygon_walls.clone(),
+            circle_obstacles: config.circle_obstacles.clone(),
+            gates: config.gates.clone(),
+            targets: config.targets.clone(),
+            target_motion: config.target_motion,
+            boundary_mode: config.boundary_mode,
+            spawn: config.spawn(),
+            spawn_angle: config.spawn_angle,
             frame_counter: 0,
             timer_rect: Rectangle::new(
                 0.0,
-                (SCREEN_HEIGHT - TIMER_RECT_HEIGHT) as f32,
-                SCREEN_WIDTH as f32,
+                (screen_height - TIMER_RECT_HEIGHT) as f32,
+                screen_width as f32,
                 TIMER_RECT_HEIGHT as f32,
             ),
             generation: 0,
+            sim_speed: 1,
+            sim_frames: 0,
+            run_time: 0.0,
+            selection_strategy: config.selection,
+            species_color: ALIVE_ROCKET_COLOR,
+            best_fitness_history: vec![],
+            fastest_success: None,
+            animate_respawn: false,
+            respawn_frames_left: 0,
+            fading_rockets: vec![],
+            first_success: None,
+            first_solved_generation: None,
+            solved_generation: None,
+            ramp_solves: vec![],
+            stagnant_generations: 0,
+            best_fitness_ever: 0.0,
+            total_successes: 0,
+            best_dna: None,
+            ghost_path: vec![],
+            gene_variance: vec![],
+            diversity: None,
+            seed_dna: None,
+            replay: None,
+            death_history: VecDeque::new(),
+            frozen_paths: VecDeque::new(),
+            history: VecDeque::new(),
+            history_pos: 0,
+            pending_gene_len: None,
+            pending_rocket_count: None,
+            stats_writer: None,
+            phase_times: None,
+            landscape: vec![],
+            landscape_target: None,
+            nav_grids: vec![],
             mating_pool: vec![],
+            run_seed,
+            config,
+            rng,
         };
-        for rocket in &mut instance.rockets {
-            rocket.dna.randomize();
+        instance.config.rocket_count = instance.config.population_size(0);
+        let mut rockets: Vec<Rocket> = (0..instance.config.rocket_count)
+            .map(|_| instance.spawn_rocket())
+            .collect();
+        for rocket in &mut rockets {
+            rocket.dna.randomize(
+                instance.config.init_distribution,
+                |ind| instance.config.degree_bound(ind),
+                instance.config.thrust,
+                &mut instance.rng,
+            );
         }
+        instance.rockets = rockets;
+        instance.reset_flight();
+        instance.record_history();
         instance
     }
 
-    fn restart(&mut self) {
-        self.calc_fitness();
-        self.gen_mating_pool();
-        let mut instance = Self::new();
-        self.selection(&mut instance.rockets);
-        instance.generation = self.generation + 1;
+    // Fresh rocket waiting at the spawn, its genes are left for the caller to fill in
+    fn spawn_rocket(&self) -> Rocket {
+        Rocket::new(
+            self.spawn,
+            self.spawn_angle,
+            self.config.gene_len,
+            self.config.gene_encoding,
+            self.config.fuel(),
+            self.config.rocket_health,
+        )
+    }
 
-        *self = instance;
+    // Sends the current rockets off from the first frame, they have to be freshly spawned
+    fn reset_flight(&mut self) {
+        self.alive_count = self.rockets.len() as i32;
+        self.frame_counter = 0;
+        self.first_success = None;
+        self.targets = self.config.targets.clone();
+        self.move_target();
+        self.refresh_landscape();
+        self.update_timer();
     }
 
-    fn calc_dist_from_target(&mut self) {
-        for rocket in &mut self.rockets {
-            let pos_diff = self.target.sub(rocket.pos);
-            let hyp = (pos_diff.x.powi(2)) + (pos_diff.y.powi(2));
-            rocket.dist_from_target = hyp.sqrt();
+    // Straight line distance is all the fitness sees, so walls don't show up in it
+    fn refresh_landscape(&mut self) {
+        let Some(&target) = self.targets.last() else {
+            return;
+        };
+        if self.landscape_target == Some(target) {
+            return;
+        }
+        let (cols, rows) = self.landscape_size();
+        let mut landscape: Vec<f32> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let center = Vector2::new(
+                    (col as f32 + 0.5) * LANDSCAPE_CELL_SIZE,
+                    (row as f32 + 0.5) * LANDSCAPE_CELL_SIZE,
+                );
+                center.sub(target).length()
+            })
+            .collect();
+        let max = landscape.iter().copied().fold(0.0, f32::max);
+        if max > f32::EPSILON {
+            landscape.iter_mut().for_each(|el| *el /= max);
         }
+        self.landscape = landscape;
+        self.landscape_target = Some(target);
     }
 
-    fn calc_fitness(&mut self) {
-        self.calc_dist_from_target();
-        let dist_from_target_sum: f32 = self.rockets.iter().map(|el| el.dist_from_target).sum();
+    // Columns and rows of landscape cells that cover the screen
+    fn landscape_size(&self) -> (usize, usize) {
+        (
+            (self.config.screen_width as f32 / LANDSCAPE_CELL_SIZE).ceil() as usize,
+            (self.config.screen_height as f32 / LANDSCAPE_CELL_SIZE).ceil() as usize,
+        )
+    }
 
+    fn restart(&mut self) {
+        self.rng = self.generation_rng(self.generation);
+        if self.replay.is_some() {
+            self.restart_replay();
+            return;
+        }
+        // Anything still flying has run out of time rather than crashed or landed
         for rocket in &mut self.rockets {
-            rocket.dna.fitness = 1.0 - (rocket.dist_from_target / dist_from_target_sum);
+            if rocket.state == RocketState::Alive {
+                rocket.state = RocketState::Timeout;
+            }
+        }
+        let start = Instant::now();
+        let best_fitness = self.calc_fitness();
+        self.record_phase(|el| &mut el.fitness, start);
+        self.best_fitness_history.push(best_fitness);
+        if best_fitness > self.best_fitness_ever {
+            self.best_fitness_ever = best_fitness;
+            self.stagnant_generations = 0;
+        } else {
+            self.stagnant_generations += 1;
+        }
+        let diversity = self.population_diversity();
+        let (_, _, successful, _) = self.state_counts();
+        if successful > 0 && self.first_solved_generation.is_none() {
+            self.first_solved_generation = Some(self.generation);
+        }
+        let success_rate = successful as f32 / self.rockets.len().max(1) as f32;
+        let solved = success_rate >= self.config.solved_success_rate;
+        if solved && self.solved_generation.is_none() {
+            self.solved_generation = Some(self.generation);
+            // Kept off stdout so it doesn't end up in a sweep's CSV
+            eprintln!(
+                "Solved: {:.0}% of rockets reached the target in generation {}",
+                success_rate * 100.0,
+                self.generation
+            );
+        }
+        if let (true, Some(target)) = (solved, self.next_ramp_target()) {
+            self.ramp_solves.push(self.generation);
+            self.place_target(target);
+            eprintln!(
+                "Difficulty: level {}, the target moved to (x: {:.0}, y: {:.0})",
+                self.ramp_solves.len(),
+                target.x,
+                target.y
+            );
+        }
+        if let Some(writer) = &mut self.stats_writer {
+            let stats = GenerationStats::from_rockets(self.generation, &self.rockets);
+            if let Err(err) = writer.write(&stats) {
+                eprintln!("WARNING: failed to write generation stats: {}", err);
+            }
+        }
+        let start = Instant::now();
+        match self.selection_strategy {
+            SelectionStrategy::Roulette => self.gen_mating_pool(),
+            SelectionStrategy::Rank => self.gen_rank_mating_pool(),
+            SelectionStrategy::Tournament { .. } => {}
+        }
+        self.record_phase(|el| &mut el.mating_pool, start);
+        if let Some(gene_len) = self.pending_gene_len.take() {
+            self.config.gene_len = gene_len;
+        }
+        // A count set on the panel wins over the schedule for the one generation
+        self.config.rocket_count = self.config.population_size(self.generation + 1);
+        if let Some(rocket_count) = self.pending_rocket_count.take() {
+            self.config.rocket_count = rocket_count;
+        }
+        // Parents are picked from the old generation whatever its size, only the elites
+        // have to fit into the new one
+        let ranked_inds = self.ranked_inds();
+        let elite_count = self
+            .config
+            .elite_count
+            .min(ranked_inds.len())
+            .min(self.config.rocket_count);
+        let mut rockets: Vec<Rocket> = (0..self.config.rocket_count)
+            .map(|_| self.spawn_rocket())
+            .collect();
+        for (rocket, &ind) in rockets.iter_mut().zip(&ranked_inds[..elite_count]) {
+            rocket.dna.genes = self.rockets[ind].dna.genes.clone();
+            rocket.dna.lineage_hue = self.rockets[ind].dna.lineage_hue;
+        }
+        let start = Instant::now();
+        self.selection(&mut rockets[elite_count..], elite_count);
+        self.record_phase(|el| &mut el.selection, start);
+        // Children of the old generation still carry its gene length, unless lengths are
+        // allowed to differ
+        if self.config.length_mutation.is_none() {
+            for rocket in &mut rockets {
+                rocket.dna.genes.resize(self.config.gene_len);
+            }
+        }
+        if let Some(catastrophe) = self.config.catastrophe {
+            if self.stagnant_generations >= catastrophe.generations {
+                let children = rockets.len() - elite_count;
+                let count = (children as f32 * catastrophe.fraction).round() as usize;
+                // Every child takes over the slot of the old rocket with its index, so the
+                // ones in the worst old rockets' slots start over
+                let worst: Vec<usize> = ranked_inds
+                    .iter()
+                    .rev()
+                    .copied()
+                    .filter(|&ind| (elite_count..rockets.len()).contains(&ind))
+                    .take(count)
+                    .collect();
+                for &ind in &worst {
+                    rockets[ind].dna.randomize(
+                        self.config.init_distribution,
+                        |ind| self.config.degree_bound(ind),
+                        self.config.thrust,
+                        &mut self.rng,
+                    );
+                }
+                // Kept off stdout so it doesn't end up in a sweep's CSV
+                eprintln!(
+                    "Catastrophe: no improvement for {} generations, randomized {} rockets",
+                    self.stagnant_generations,
+                    worst.len()
+                );
+                self.stagnant_generations = 0;
+            }
+        }
+        // Takes the last bred child's slot, elites stay untouched. Planted after the
+        // catastrophe so it can't be randomized away
+        if let Some(dna) = self.seed_dna.take() {
+            match rockets[elite_count..].last_mut() {
+                Some(rocket) => {
+                    rocket.dna.genes = dna.genes;
+                    if self.config.length_mutation.is_none() {
+                        rocket.dna.genes.resize(self.config.gene_len);
+                    }
+                }
+                None => {
+                    eprintln!("WARNING: every rocket is an elite, the loaded genome was dropped")
+                }
+            }
         }
-    }
-
-    fn gen_mating_pool(&mut self) {
-        self.mating_pool.clear();
 
-        for (ind, rocket) in self.rockets.iter().enumerate() {
-            let n = rocket.dna.fitness * 100.0;
-            let n = match rocket.state {
-                RocketState::Dead => n * (0.6),
-                RocketState::Alive => n,
-                RocketState::Successful => n * 2.0,
-            };
-            for _ in 0..(n.floor() as usize) {
-                self.mating_pool.push(ind);
+        self.total_successes += successful;
+        self.death_history
+            .push_back(self.rockets.iter().filter_map(|el| el.death_pos).collect());
+        if self.death_history.len() > DEATH_HISTORY_LEN {
+            self.death_history.pop_front();
+        }
+        if let Some(fastest) = self
+            .rockets
+            .iter()
+            .filter_map(|el| el.frames_to_success.map(|frames| (frames, el)))
+            .min_by_key(|&(frames, _)| frames)
+            .map(|(_, el)| el.path.clone())
+        {
+            self.frozen_paths.push_back((self.generation, fastest));
+            if self.frozen_paths.len() > FROZEN_PATHS_LEN {
+                self.frozen_paths.pop_front();
             }
         }
+        self.gene_variance = self.gene_variance();
+        self.diversity = Some(diversity);
+        if let Some(&best_ind) = ranked_inds.first() {
+            self.best_dna = Some(self.rockets[best_ind].dna.clone());
+            self.ghost_path = std::mem::take(&mut self.rockets[best_ind].path);
+        }
+        let previous = std::mem::replace(&mut self.rockets, rockets);
+        if self.animate_respawn && self.sim_speed == 1 {
+            self.fading_rockets = previous;
+            self.respawn_frames_left = RESPAWN_FRAMES;
+        }
+        self.generation += 1;
+        self.reset_flight();
+        // Evolving on from an older snapshot drops the generations that came after it
+        self.history.truncate(self.history_pos + 1);
+        self.record_history();
     }
 
-    fn selection(&self, rockets: &mut [Rocket]) {
-        for rocket in rockets.iter_mut() {
-            let mut rocket_inst = Rocket::new(Vector2::new(
-                (SCREEN_WIDTH / 2) as f32,
-                (SCREEN_HEIGHT - 75) as f32,
-            ));
-
-            let mut rng = rand::thread_rng();
-            let rand_a = rng.gen_range(0..self.mating_pool.len());
-            let rand_b = rng.gen_range(0..self.mating_pool.len());
-            let parent_a_ind = self.mating_pool[rand_a];
-            let parent_b_ind = self.mating_pool[rand_b];
-            rocket_inst.dna = DNA::crossover(
-                &self.rockets[parent_a_ind].dna,
-                &self.rockets[parent_b_ind].dna,
-            );
-            DNA::mutate(&mut rocket_inst.dna);
+    // Adds the time since `start` to one phase of the running benchmark, if there is one
+    fn record_phase(&mut self, phase: fn(&mut PhaseTimes) -> &mut Duration, start: Instant) {
+        if let Some(times) = &mut self.phase_times {
+            *phase(times) += start.elapsed();
+        }
+    }
 
-            *rocket = rocket_inst;
+    fn record_history(&mut self) {
+        let genomes = self.rockets.iter().map(|el| el.dna.genes.clone()).collect();
+        self.history.push_back((self.generation, genomes));
+        if self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
         }
+        self.history_pos = self.history.len() - 1;
     }
 
-    fn collision_rocket(&self, ind: usize) -> bool {
-        self.collision_world(&self.rockets[ind].pos) || self.collision_wall(&self.rockets[ind].pos)
+    // Flies the snapshot `forward` or backward from the current one again from its first frame
+    fn step_history(&mut self, forward: bool) {
+        let pos = if forward {
+            self.history_pos + 1
+        } else {
+            match self.history_pos.checked_sub(1) {
+                Some(pos) => pos,
+                None => return,
+            }
+        };
+        let Some((generation, genomes)) = self.history.get(pos) else {
+            return;
+        };
+        let rockets = genomes
+            .iter()
+            .map(|genes| {
+                let mut rocket = self.spawn_rocket();
+                rocket.dna.genes = genes.clone();
+                rocket
+            })
+            .collect();
+        self.generation = *generation;
+        self.rockets = rockets;
+        self.history_pos = pos;
+        self.reset_flight();
     }
 
-    fn collision_world(&self, pos: &Vector2) -> bool {
-        pos.x < 0.0 || pos.x > SCREEN_WIDTH as f32 || pos.y < 0.0 || pos.y > SCREEN_HEIGHT as f32
+    // Puts the replayed rockets back at the start of their flight
+    fn restart_replay(&mut self) {
+        let Some(saved) = &self.replay else {
+            return;
+        };
+        let rockets = saved
+            .genomes
+            .iter()
+            .map(|genes| {
+                let mut rocket = self.spawn_rocket();
+                rocket.dna.genes = genes.clone();
+                rocket
+            })
+            .collect();
+        self.generation = saved.generation;
+        self.rockets = rockets;
+        self.reset_flight();
     }
 
-    fn collision_wall(&self, pos: &Vector2) -> bool {
-        for wall in &self.walls {
-            if pos.x > wall.x
-                && pos.x < wall.x + wall.width
-                && pos.y > wall.y
-                && pos.y < wall.y + wall.height
-            {
-                return true;
-            }
+    // Frames a generation flies for, long enough for its longest genome
+    fn flight_len(&self) -> u32 {
+        if self.config.length_mutation.is_none() {
+            return self.config.gene_len as u32;
         }
-        false
+        self.rockets
+            .iter()
+            .map(|el| el.dna.genes.len())
+            .max()
+            .unwrap_or(self.config.gene_len) as u32
     }
 
-    fn collision_target(&self, ind: usize) -> bool {
-        let pos = self.rockets[ind].pos;
-        let diff = self.target.sub(pos);
-        let dist_from_center = (diff.x.powi(2) + diff.y.powi(2)).sqrt();
-        dist_from_center < TARGET_RADIUS
+    // Share of the generation's frames still to come, from 1 down to 0
+    fn time_left(&self) -> f32 {
+        let flight_len = self.flight_len();
+        let frames_left = flight_len.saturating_sub(self.frame_counter);
+        frames_left as f32 / flight_len.max(1) as f32
     }
-}
-
-enum Actions {
-    Pause,
-    Reset,
-    Nothing,
-}
 
-fn handle_input(rl: &RaylibHandle) -> Actions {
-    if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-        return Actions::Pause;
+    fn update_timer(&mut self) {
+        self.timer_rect.width = self.config.screen_width as f32 * self.time_left();
     }
-    if rl.is_key_pressed(KeyboardKey::KEY_R) {
-        return Actions::Reset;
+
+    fn move_target(&mut self) {
+        let (Some(&anchor), Some(target)) = (self.config.targets.last(), self.targets.last_mut())
+        else {
+            return;
+        };
+        let time = self.frame_counter as f32;
+        *target = match self.target_motion {
+            TargetMotion::Static => return,
+            TargetMotion::Circle {
+                center,
+                radius,
+                speed,
+            } => Vector2::new(
+                center.x + radius * (speed * time).cos(),
+                center.y + radius * (speed * time).sin(),
+            ),
+            TargetMotion::Oscillate {
+                axis,
+                amplitude,
+                speed,
+            } => {
+                let offset = amplitude * (speed * time).sin();
+                match axis {
+                    Axis::X => Vector2::new(anchor.x + offset, anchor.y),
+                    Axis::Y => Vector2::new(anchor.x, anchor.y + offset),
+                }
+            }
+        };
     }
-    Actions::Nothing
-}
 
-fn update(world: &mut World) {
-    if world.frame_counter == GENE_LEN as u32 {
-        world.restart();
-        return;
+    fn calc_dist_from_target(&mut self) {
+        self.refresh_nav_grids();
+        let targets = &self.targets;
+        let nav_grids = &self.nav_grids;
+        self.rockets.par_iter_mut().for_each(|rocket| {
+            rocket.dist_from_target = match nav_grids.get(rocket.next_target) {
+                Some(grid) => grid.distance(rocket.pos),
+                None => {
+                    let pos_diff = targets[rocket.next_target].sub(rocket.pos);
+                    let hyp = (pos_diff.x.powi(2)) + (pos_diff.y.powi(2));
+                    hyp.sqrt()
+                }
+            };
+        });
     }
-    let mut dead_inds: Vec<usize> = vec![];
-    let mut succ_inds: Vec<usize> = vec![];
-    for ind in 0..world.rockets.len() {
-        if world.collision_rocket(ind) {
-            dead_inds.push(ind);
-            continue;
+
+    // Rebuilds the path distance field of every target that has moved since it was built
+    fn refresh_nav_grids(&mut self) {
+        if !self.config.path_distance {
+            return;
         }
-        if world.collision_target(ind) {
-            succ_inds.push(ind);
-            continue;
+        self.nav_grids.truncate(self.targets.len());
+        for (ind, &target) in self.targets.iter().enumerate() {
+            if self.nav_grids.get(ind).is_some_and(|el| el.goal == target) {
+                continue;
+            }
+            let grid = NavGrid::new(
+                &self.walls,
+                &self.polygon_walls,
+                &self.circle_obstacles,
+                self.config.screen_width as f32,
+                self.config.screen_height as f32,
+                NAV_GRID_CELL_SIZE,
+                target,
+            );
+            if ind < self.nav_grids.len() {
+                self.nav_grids[ind] = grid;
+            } else {
+                self.nav_grids.push(grid);
+            }
         }
     }
 
-    for (ind, rocket) in world.rockets.iter_mut().enumerate() {
-        if dead_inds.contains(&ind) {
-            if rocket.state == RocketState::Alive {
+    // Returns the best fitness before normalization so generations can be compared
+    // Sums and maxes stay serial so the float results don't depend on the thread count
+    fn calc_fitness(&mut self) -> f32 {
+        self.calc_dist_from_target();
+        match self.config.fitness_kind {
+            FitnessKind::Relative => {
+                let dist_from_target_sum: f32 =
+                    self.rockets.iter().map(|el| el.dist_from_target).sum();
+                self.rockets.par_iter_mut().for_each(|rocket| {
+                    // Every rocket sitting on the target would divide by zero
+                    rocket.dna.fitness = if dist_from_target_sum > f32::EPSILON {
+                        1.0 - (rocket.dist_from_target / dist_from_target_sum)
+                    } else {
+                        1.0
+                    };
+                });
+            }
+            FitnessKind::Inverse => {
+                self.rockets.par_iter_mut().for_each(|rocket| {
+                    rocket.dna.fitness = 1.0 / (rocket.dist_from_target + 1.0);
+                });
+            }
+            FitnessKind::Squared => {
+                let max_dist = self
+                    .rockets
+                    .iter()
+                    .map(|el| el.dist_from_target)
+                    .fold(0.0, f32::max);
+                self.rockets.par_iter_mut().for_each(|rocket| {
+                    rocket.dna.fitness = (max_dist - rocket.dist_from_target).powi(2);
+                });
+            }
+        }
+
+        let config = &self.config;
+        self.rockets.par_iter_mut().for_each(|rocket| {
+            rocket.dna.fitness = adjusted_fitness(rocket.dna.fitness, rocket, config);
+        });
+
+        let max_fitness = self
+            .rockets
+            .iter()
+            .map(|el| el.dna.fitness)
+            .fold(0.0, f32::max);
+        if self.config.fitness_kind != FitnessKind::Relative {
+            self.rockets.par_iter_mut().for_each(|rocket| {
+                // Nothing to tell rockets apart by, so treat them all the same
+                if max_fitness > f32::EPSILON {
+                    rocket.dna.fitness /= max_fitness;
+                } else {
+                    rocket.dna.fitness = 1.0;
+                }
+            });
+        }
+        max_fitness
+    }
+
+    // Score the rocket would get under `FitnessKind::Inverse` if the generation ended now,
+    // before it's scaled against the rest of the generation
+    fn live_fitness(&self, rocket: &Rocket) -> f32 {
+        adjusted_fitness(1.0 / (rocket.dist_from_target + 1.0), rocket, &self.config)
+    }
+
+    // Indices of the `LEADERBOARD_LEN` rockets with the best `live_fitness` along with it,
+    // best first and ties going to the lower index
+    fn leaderboard(&self) -> Vec<(usize, f32)> {
+        let mut scores: Vec<(usize, f32)> = self
+            .rockets
+            .iter()
+            .enumerate()
+            .map(|(ind, rocket)| (ind, self.live_fitness(rocket)))
+            .collect();
+        let order = |a: &(usize, f32), b: &(usize, f32)| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0));
+        let len = LEADERBOARD_LEN.min(scores.len());
+        if len < scores.len() {
+            scores.select_nth_unstable_by(len, order);
+        }
+        scores.truncate(len);
+        scores.sort_by(order);
+        scores
+    }
+
+    fn adaptive_mutation_rate(&self) -> f32 {
+        let history = &self.best_fitness_history;
+        if history.len() <= STAGNATION_GENERATIONS {
+            return self.config.mutation_rate;
+        }
+        let (older, recent) = history.split_at(history.len() - STAGNATION_GENERATIONS);
+        let best_older = older.iter().copied().fold(f32::MIN, f32::max);
+        let best_recent = recent.iter().copied().fold(f32::MIN, f32::max);
+        if best_recent > best_older {
+            self.config.mutation_rate
+        } else {
+            STAGNANT_MUTATION_RATE
+        }
+    }
+
+    fn gene_variance(&self) -> Vec<f32> {
+        let count = self.rockets.len() as f32;
+        let gene_len = self
+            .rockets
+            .iter()
+            .map(|el| el.dna.genes.len())
+            .min()
+            .unwrap_or(0);
+        (0..gene_len)
+            .map(|ind| {
+                let mean = self
+                    .rockets
+                    .iter()
+                    .fold(Vector2::zero(), |acc, el| acc + el.dna.genes.as_vector(ind))
+                    / count;
+                self.rockets
+                    .iter()
+                    .map(|el| (el.dna.genes.as_vector(ind) - mean).length_sqr())
+                    .sum::<f32>()
+                    / count
+            })
+            .collect()
+    }
+
+    // Root mean square difference between the genes of two rockets
+    fn genome_distance(&self, a: usize, b: usize) -> f32 {
+        let (genes_a, genes_b) = (&self.rockets[a].dna.genes, &self.rockets[b].dna.genes);
+        let gene_len = genes_a.len().min(genes_b.len());
+        if gene_len == 0 {
+            return 0.0;
+        }
+        let sum: f32 = (0..gene_len)
+            .map(|ind| (genes_a.as_vector(ind) - genes_b.as_vector(ind)).length_sqr())
+            .sum();
+        (sum / gene_len as f32).sqrt()
+    }
+
+    // Average genome distance over every pair of rockets, or over a random sample
+    // of pairs once there are too many to check them all
+    fn population_diversity(&mut self) -> f32 {
+        let count = self.rockets.len();
+        if count < 2 {
+            return 0.0;
+        }
+        let pairs: Vec<(usize, usize)> = if count * (count - 1) / 2 <= DIVERSITY_SAMPLE_PAIRS {
+            (0..count)
+                .flat_map(|a| (a + 1..count).map(move |b| (a, b)))
+                .collect()
+        } else {
+            (0..DIVERSITY_SAMPLE_PAIRS)
+                .map(|_| {
+                    let a = self.rng.gen_range(0..count);
+                    // Offsetting by 1..count never picks the same rocket twice
+                    let b = (a + self.rng.gen_range(1..count)) % count;
+                    (a, b)
+                })
+                .collect()
+        };
+        let sum: f32 = pairs.iter().map(|&(a, b)| self.genome_distance(a, b)).sum();
+        sum / pairs.len() as f32
+    }
+
+    // Rocket indices ordered from fittest to least fit
+    fn ranked_inds(&self) -> Vec<usize> {
+        let mut inds: Vec<usize> = (0..self.rockets.len()).collect();
+        inds.sort_by(|&a, &b| {
+            let fitness_a = self.rockets[a].dna.fitness;
+            let fitness_b = self.rockets[b].dna.fitness;
+            fitness_b.total_cmp(&fitness_a)
+        });
+        inds
+    }
+
+    fn save_best(&self, path: &str) -> Result<(), String> {
+        let dna = self
+            .best_dna
+            .as_ref()
+            .ok_or("no generation has finished yet")?;
+        let json = serde_json::to_string_pretty(dna).map_err(|err| err.to_string())?;
+        fs::write(path, json).map_err(|err| format!("failed to write '{}': {}", path, err))
+    }
+
+    fn save_generation(&self, path: &str) -> Result<(), String> {
+        let saved = SavedGeneration {
+            generation: self.generation,
+            genomes: self.rockets.iter().map(|el| el.dna.genes.clone()).collect(),
+            targets: self.config.targets.clone(),
+            target_radius: Some(self.config.target_radius),
+            walls: self.walls.clone(),
+            polygon_walls: self.polygon_walls.clone(),
+            circle_obstacles: self.circle_obstacles.clone(),
+            gates: self.gates.clone(),
+        };
+        let json = serde_json::to_string_pretty(&saved).map_err(|err| err.to_string())?;
+        fs::write(path, json).map_err(|err| format!("failed to write '{}': {}", path, err))
+    }
+
+    // Switches the world over to replaying the saved generation from its first frame
+    fn load_generation(&mut self, path: &str) -> Result<(), String> {
+        let json = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read '{}': {}", path, err))?;
+        let saved: SavedGeneration = serde_json::from_str(&json)
+            .map_err(|err| format!("failed to parse '{}': {}", path, err))?;
+        let Some(first) = saved.genomes.first() else {
+            return Err(format!("'{}' doesn't hold any genomes", path));
+        };
+        let encoding = first.encoding();
+        if saved.genomes.iter().any(|el| el.len() == 0) {
+            return Err(format!("'{}' holds empty genomes", path));
+        }
+        if saved.genomes.iter().any(|el| el.encoding() != encoding) {
+            return Err(format!("genomes in '{}' don't share one encoding", path));
+        }
+        // Genomes saved with length mutation on can differ, the flight lasts for the longest
+        let gene_len = saved.genomes.iter().map(Genes::len).max().unwrap_or(0);
+        if saved.targets.is_empty() {
+            return Err(format!("'{}' doesn't hold any targets", path));
+        }
+        if saved.target_radius.is_some_and(|radius| radius <= 0.0) {
+            return Err(format!(
+                "'{}' holds a target radius that isn't positive",
+                path
+            ));
+        }
+
+        self.config.gene_len = gene_len;
+        self.config.gene_encoding = encoding;
+        self.config.rocket_count = saved.genomes.len();
+        self.config.targets = saved.targets.clone();
+        if let Some(radius) = saved.target_radius {
+            self.config.target_radius = radius;
+        }
+        self.config.walls = saved.walls.clone();
+        self.config.polygon_walls = saved.polygon_walls.clone();
+        self.config.circle_obstacles = saved.circle_obstacles.clone();
+        self.config.gates = saved.gates.clone();
+        self.walls = saved.walls.clone();
+        self.rebuild_wall_grid();
+        self.polygon_walls = saved.polygon_walls.clone();
+        self.circle_obstacles = saved.circle_obstacles.clone();
+        self.gates = saved.gates.clone();
+        self.replay = Some(saved);
+        self.restart_replay();
+        // Nothing from the generation that was flying before carries over
+        self.ghost_path.clear();
+        self.history.clear();
+        self.record_history();
+        Ok(())
+    }
+
+    // Rebuilds what a loaded world left out of its save
+    fn restore(&mut self) {
+        self.rng = self.generation_rng(self.generation);
+        self.rebuild_wall_grid();
+        self.refresh_landscape();
+    }
+
+    fn load_best(&mut self, path: &str) -> Result<(), String> {
+        self.seed_dna = Some(self.read_genome(path)?);
+        Ok(())
+    }
+
+    fn read_genome(&mut self, path: &str) -> Result<DNA, String> {
+        let json = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read '{}': {}", path, err))?;
+        let mut dna: DNA = serde_json::from_str(&json)
+            .map_err(|err| format!("failed to parse '{}': {}", path, err))?;
+        if dna.genes.encoding() != self.config.gene_encoding {
+            return Err(format!(
+                "'{}' holds {:?} genes but the config uses {:?}",
+                path,
+                dna.genes.encoding(),
+                self.config.gene_encoding
+            ));
+        }
+        // Genomes saved with a different gene length are cut short or topped up with random
+        // genes, lengths that are allowed to vary only have to fit in their range
+        let len = match self.config.length_mutation {
+            Some(length) => dna.genes.len().clamp(length.min_len, length.max_len),
+            None => self.config.gene_len,
+        };
+        dna.fit_to_len(
+            len,
+            self.config.init_distribution,
+            |ind| self.config.degree_bound(ind),
+            self.config.thrust,
+            &mut self.rng,
+        );
+        Ok(dna)
+    }
+
+    // Spreads every genome saved in `dir` evenly over the first generation, which flies them
+    // unmutated next to the random rockets. Returns how many were planted
+    fn init_from(&mut self, dir: &str) -> Result<usize, String> {
+        let entries =
+            fs::read_dir(dir).map_err(|err| format!("failed to read '{}': {}", dir, err))?;
+        let mut paths: Vec<_> = entries
+            .filter_map(|el| el.ok().map(|el| el.path()))
+            .filter(|el| el.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        // Sorted so the same directory always plants the same rockets
+        paths.sort();
+        if paths.is_empty() {
+            return Err(format!("'{}' doesn't hold any .json genomes", dir));
+        }
+        let count = self.rockets.len();
+        if paths.len() > count {
+            eprintln!(
+                "WARNING: '{}' holds {} genomes, only the first {} fit in the population",
+                dir,
+                paths.len(),
+                count
+            );
+            paths.truncate(count);
+        }
+        let genomes = paths
+            .iter()
+            .map(|el| self.read_genome(&el.to_string_lossy()))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (ind, dna) in genomes.iter().enumerate() {
+            let rocket = &mut self.rockets[ind * count / genomes.len()];
+            rocket.dna.genes = dna.genes.clone();
+        }
+        // The snapshot of the first generation has to show the planted genomes
+        self.history.clear();
+        self.history_pos = 0;
+        self.record_history();
+        Ok(genomes.len())
+    }
+
+    fn gen_mating_pool(&mut self) {
+        self.mating_pool.clear();
+
+        let weights = self.config.state_weights;
+        for (ind, rocket) in self.rockets.iter().enumerate() {
+            let n = rocket.dna.fitness * 100.0;
+            let n = match rocket.state {
+                RocketState::Dead => n * weights.dead,
+                // Only a replay scores rockets that are still flying
+                RocketState::Alive | RocketState::Timeout => n * weights.timeout,
+                RocketState::Successful => n * weights.successful,
+            };
+            for _ in 0..(n.floor() as usize) {
+                self.mating_pool.push(ind);
+            }
+        }
+    }
+
+    // Linear ranking: out of n rockets the fittest gets n entries, the next n - 1 and so
+    // on down to a single entry for the least fit, so how far apart the fitness values
+    // are doesn't matter, only their order
+    fn gen_rank_mating_pool(&mut self) {
+        self.mating_pool.clear();
+
+        let ranked_inds = self.ranked_inds();
+        let count = ranked_inds.len();
+        for (rank, &ind) in ranked_inds.iter().enumerate() {
+            for _ in 0..(count - rank) {
+                self.mating_pool.push(ind);
+            }
+        }
+    }
+
+    // Fills `rockets` with children of the current generation, `first_ind` is where they
+    // start in the next one
+    fn selection(&self, rockets: &mut [Rocket], first_ind: usize) {
+        let mutation_rate = self.adaptive_mutation_rate();
+        rockets
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(ind, rocket)| *rocket = self.breed_child(first_ind + ind, mutation_rate));
+    }
+
+    // Child at `ind` of the next generation, which only depends on the current generation
+    // and `ind`, so any one child can be bred again on its own and a run comes out the same
+    // however the children are split between threads
+    fn breed_child(&self, ind: usize, mutation_rate: f32) -> Rocket {
+        let rng = &mut self.child_rng(self.generation + 1, ind);
+        let mut rocket = self.spawn_rocket();
+
+        let parent_a = &self.rockets[self.pick_parent(rng)].dna;
+        let parent_b = &self.rockets[self.pick_parent(rng)].dna;
+        // A rate of 1 skips the draw, so runs from before it existed fly the same
+        let crossover_rate = self.config.crossover_rate;
+        rocket.dna = if crossover_rate >= 1.0 || rng.gen::<f32>() < crossover_rate {
+            DNA::crossover(self.config.crossover, parent_a, parent_b, rng)
+        } else {
+            DNA::clone_of(parent_a)
+        };
+        DNA::mutate(
+            &mut rocket.dna,
+            self.config.mutation,
+            mutation_rate,
+            |ind| self.config.degree_bound(ind),
+            self.config.thrust,
+            rng,
+        );
+        if let Some(length) = self.config.length_mutation {
+            DNA::mutate_length(
+                &mut rocket.dna,
+                length,
+                |ind| self.config.degree_bound(ind),
+                self.config.thrust,
+                rng,
+            );
+        }
+        rocket
+    }
+
+    // Every (run seed, generation, index) gets its own ChaCha key, so no two children share
+    // a stream
+    fn child_rng(&self, generation: u32, ind: usize) -> StdRng {
+        let mut seed = [0; 32];
+        seed[..8].copy_from_slice(&self.run_seed.to_le_bytes());
+        seed[8..12].copy_from_slice(&generation.to_le_bytes());
+        seed[16..24].copy_from_slice(&(ind as u64).to_le_bytes());
+        StdRng::from_seed(seed)
+    }
+
+    // Rng the world draws from outside of breeding, the last byte keeps it apart from every
+    // child's rng of the same generation
+    fn generation_rng(&self, generation: u32) -> StdRng {
+        let mut seed = [0; 32];
+        seed[..8].copy_from_slice(&self.run_seed.to_le_bytes());
+        seed[8..12].copy_from_slice(&generation.to_le_bytes());
+        seed[31] = 1;
+        StdRng::from_seed(seed)
+    }
+
+    fn pick_parent(&self, rng: &mut StdRng) -> usize {
+        match self.selection_strategy {
+            // Every fitness can floor to zero entries, so fall back to uniform picking
+            SelectionStrategy::Roulette | SelectionStrategy::Rank
+                if self.mating_pool.is_empty() =>
+            {
+                rng.gen_range(0..self.rockets.len())
+            }
+            SelectionStrategy::Roulette | SelectionStrategy::Rank => {
+                let rand_ind = rng.gen_range(0..self.mating_pool.len());
+                self.mating_pool[rand_ind]
+            }
+            SelectionStrategy::Tournament { k } => {
+                // Pick k random contenders and keep the fittest one
+                let mut best_ind = rng.gen_range(0..self.rockets.len());
+                for _ in 1..k {
+                    let ind = rng.gen_range(0..self.rockets.len());
+                    if self.rockets[ind].dna.fitness > self.rockets[best_ind].dna.fitness {
+                        best_ind = ind;
+                    }
+                }
+                best_ind
+            }
+        }
+    }
+
+    // Returns how many rockets are (alive, dead, successful, timed out)
+    fn state_counts(&self) -> (usize, usize, usize, usize) {
+        let mut counts = (0, 0, 0, 0);
+        for rocket in &self.rockets {
+            match rocket.state {
+                RocketState::Alive => counts.0 += 1,
+                RocketState::Dead => counts.1 += 1,
+                RocketState::Successful => counts.2 += 1,
+                RocketState::Timeout => counts.3 += 1,
+            }
+        }
+        counts
+    }
+
+    fn closest_dist_to_target(&self) -> Option<f32> {
+        self.rockets
+            .iter()
+            .map(|el| self.targets[el.next_target].sub(el.pos).length())
+            .reduce(f32::min)
+    }
+
+    // Alive rocket closest to the target it's heading for, none once they've all landed
+    fn leading_rocket(&self) -> Option<&Rocket> {
+        self.rockets
+            .iter()
+            .filter(|el| el.state == RocketState::Alive)
+            .min_by(|a, b| a.dist_from_target.total_cmp(&b.dist_from_target))
+    }
+
+    // Moves the final target, the config is updated too so it stays there after a restart
+    fn place_target(&mut self, pos: Vector2) {
+        let pos = Vector2::new(
+            pos.x.clamp(0.0, self.config.screen_width as f32),
+            pos.y.clamp(0.0, self.config.screen_height as f32),
+        );
+        if let (Some(anchor), Some(target)) =
+            (self.config.targets.last_mut(), self.targets.last_mut())
+        {
+            *anchor = pos;
+            *target = pos;
+        }
+        self.move_target();
+        self.refresh_landscape();
+    }
+
+    // Where the final target goes once the current difficulty is solved, none once the ramp
+    // has nowhere harder left to go
+    fn next_ramp_target(&self) -> Option<Vector2> {
+        let current = *self.config.targets.last()?;
+        match self.config.difficulty_ramp.as_ref()? {
+            DifficultyRamp::Targets { targets } => targets.get(self.ramp_solves.len()).copied(),
+            DifficultyRamp::Distance { step } => {
+                let away = current - self.spawn;
+                if away.length() == 0.0 {
+                    return None;
+                }
+                let margin = self.config.target_radius;
+                let next = current + away.normalized() * *step;
+                let next = Vector2::new(
+                    next.x
+                        .clamp(margin, self.config.screen_width as f32 - margin),
+                    next.y
+                        .clamp(margin, self.config.screen_height as f32 - margin),
+                );
+                // Pinned against the edge of the screen
+                (next.sub(current).length() >= 1.0).then_some(next)
+            }
+        }
+    }
+
+    fn end_respawn_fade(&mut self) {
+        self.respawn_frames_left = 0;
+        self.fading_rockets.clear();
+    }
+
+    // Crashes every rocket still flying where it is, the next update then scores them and
+    // starts the next generation
+    fn kill_all(&mut self) {
+        for rocket in &mut self.rockets {
+            if rocket.state == RocketState::Alive {
+                rocket.crash();
+            }
+        }
+        self.alive_count = 0;
+    }
+
+    fn rebuild_wall_grid(&mut self) {
+        self.wall_grid = SpatialGrid::new(
+            &self.walls,
+            self.config.screen_width as f32,
+            self.config.screen_height as f32,
+            WALL_GRID_CELL_SIZE,
+        );
+        self.nav_grids.clear();
+    }
+
+    fn add_wall(&mut self, wall: Rectangle) {
+        self.walls.push(wall);
+        self.rebuild_wall_grid();
+    }
+
+    // Moves the wall's top left corner to `corner`, keeping the whole wall on screen
+    fn move_wall(&mut self, ind: usize, corner: Vector2) {
+        let (width, height) = (
+            self.config.screen_width as f32,
+            self.config.screen_height as f32,
+        );
+        let Some(wall) = self.walls.get_mut(ind) else {
+            return;
+        };
+        let x = corner.x.clamp(0.0, (width - wall.width).max(0.0));
+        let y = corner.y.clamp(0.0, (height - wall.height).max(0.0));
+        if x != wall.x || y != wall.y {
+            wall.x = x;
+            wall.y = y;
+            self.rebuild_wall_grid();
+        }
+    }
+
+    // Removes the topmost wall under `pos`, if any
+    fn remove_wall_at(&mut self, pos: Vector2) {
+        if let Some(ind) = self
+            .walls
+            .iter()
+            .rposition(|wall| wall.check_collision_point_rec(pos))
+        {
+            self.walls.remove(ind);
+            self.rebuild_wall_grid();
+        }
+    }
+
+    fn collision_rocket(&self, ind: usize) -> Option<Collision> {
+        let rocket = &self.rockets[ind];
+        let body = rocket.bounding_box();
+        if (self.boundary_mode == BoundaryMode::Kill && self.collision_world(&rocket.pos, &body))
+            || self.collision_circle(&body)
+            || self.collision_gate(&body)
+        {
+            return Some(Collision::Obstacle);
+        }
+        if let Some(push) = self.collision_wall(&body, &collision::rocket_obb(rocket)) {
+            return Some(Collision::Wall(push));
+        }
+        if self.collision_target(ind) {
+            return Some(Collision::Target);
+        }
+        None
+    }
+
+    // `body` is the box around the rotated rocket, only looked at with `BoundaryCheck::Body`
+    fn collision_world(&self, pos: &Vector2, body: &Rectangle) -> bool {
+        let (width, height) = (
+            self.config.screen_width as f32,
+            self.config.screen_height as f32,
+        );
+        match self.config.boundary_check {
+            BoundaryCheck::Center => pos.x < 0.0 || pos.x > width || pos.y < 0.0 || pos.y > height,
+            BoundaryCheck::Body => {
+                body.x < 0.0
+                    || body.x + body.width > width
+                    || body.y < 0.0
+                    || body.y + body.height > height
+            }
+        }
+    }
+
+    // Returns how to push `body` back out of the first wall it overlaps
+    // `body` is the box around the rotated `corners`, it finds the walls worth testing
+    // against the exact shape and how far out to push the rocket
+    fn collision_wall(&self, body: &Rectangle, corners: &[Vector2; 4]) -> Option<Vector2> {
+        let mut push = None;
+        self.wall_grid.any_near(body, |ind| {
+            let wall = &self.walls[ind];
+            if wall.check_collision_recs(body) && collision::obb_overlaps_rect(corners, wall) {
+                push = Some(push_out(body, wall));
+            }
+            push.is_some()
+        });
+        if push.is_some() {
+            return push;
+        }
+        self.polygon_walls.iter().find_map(|polygon| {
+            let bounds = collision::polygon_bounds(polygon);
+            if !bounds.check_collision_recs(body) {
+                return None;
+            }
+            collision::convex_push_out(corners, polygon)
+        })
+    }
+
+    fn collision_circle(&self, body: &Rectangle) -> bool {
+        self.circle_obstacles.iter().any(|&(center, radius)| {
+            let closest = Vector2::new(
+                center.x.clamp(body.x, body.x + body.width),
+                center.y.clamp(body.y, body.y + body.height),
+            );
+            closest.sub(center).length() < radius
+        })
+    }
+
+    fn collision_gate(&self, body: &Rectangle) -> bool {
+        self.gates
+            .iter()
+            .any(|gate| !gate.is_open(self.frame_counter) && gate.rect.check_collision_recs(body))
+    }
+
+    fn collision_target(&self, ind: usize) -> bool {
+        let rocket = &self.rockets[ind];
+        let diff = self.targets[rocket.next_target].sub(rocket.pos);
+        let dist_from_center = (diff.x.powi(2) + diff.y.powi(2)).sqrt();
+        dist_from_center < self.config.target_radius
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DeathOverlay {
+    Off,
+    // A cross where each rocket of the current generation died
+    Crosses,
+    // How many rockets died in each cell over the last `DEATH_HISTORY_LEN` generations
+    Density,
+}
+
+impl DeathOverlay {
+    fn next(self) -> Self {
+        match self {
+            DeathOverlay::Off => DeathOverlay::Crosses,
+            DeathOverlay::Crosses => DeathOverlay::Density,
+            DeathOverlay::Density => DeathOverlay::Off,
+        }
+    }
+}
+
+struct View {
+    pub show_trails: bool,
+    pub show_graph: bool,
+    pub show_heatmap: bool,
+    pub show_distances: bool,
+    pub show_minimap: bool,
+    pub show_panel: bool,
+    pub show_leader: bool,
+    // Color rockets by lineage instead of by species
+    pub show_lineage: bool,
+    // Lighten live rockets the longer they have been flying
+    pub age_tint: bool,
+    pub show_tooltip: bool,
+    pub show_landscape: bool,
+    pub death_overlay: DeathOverlay,
+    pub show_leaderboard: bool,
+    // Pin the fastest successful flight of recent generations on screen
+    pub show_frozen_paths: bool,
+    pub triangle_rockets: bool,
+    // Show the time left as a ring around the final target instead of the bottom bar
+    pub timer_ring: bool,
+    // Leave long exposure trails on a canvas that fades instead of being cleared
+    pub spray_trails: bool,
+    // Stop for a moment when a generation's first rocket reaches the target
+    pub victory_pause: bool,
+    // No vsync, and every frame steps the simulation however short it was
+    pub uncapped: bool,
+    // (world, rocket, seconds left) of the victory pause that is currently running
+    pub victory: Option<(usize, usize, f32)>,
+    pub edit_mode: bool,
+    // Corner where the wall currently being dragged out was started
+    pub drag_start: Option<Vector2>,
+    // (wall, its corner relative to the cursor) of the wall being moved with the mouse
+    pub grabbed_wall: Option<(usize, Vector2)>,
+    // Only changes what is drawn, the simulation always runs in world coordinates
+    pub camera: Camera2D,
+    // Screen position of the mouse on the previous frame of a middle-drag pan
+    pub pan_last: Option<Vector2>,
+    // Width of one side when two worlds are shown next to each other
+    pub split_width: Option<f32>,
+}
+
+impl View {
+    fn new() -> Self {
+        Self {
+            show_trails: true,
+            show_graph: true,
+            show_heatmap: false,
+            show_distances: false,
+            show_minimap: false,
+            show_panel: false,
+            show_leader: false,
+            show_lineage: false,
+            age_tint: false,
+            show_tooltip: false,
+            show_landscape: false,
+            death_overlay: DeathOverlay::Off,
+            show_leaderboard: false,
+            show_frozen_paths: true,
+            triangle_rockets: false,
+            timer_ring: false,
+            spray_trails: false,
+            victory_pause: false,
+            uncapped: false,
+            victory: None,
+            edit_mode: false,
+            drag_start: None,
+            grabbed_wall: None,
+            camera: Camera2D {
+                offset: Vector2::zero(),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 1.0,
+            },
+            pan_last: None,
+            split_width: None,
+        }
+    }
+
+    // Both sides of a split screen share the camera, so the cursor is folded into the left one
+    fn mouse_position(&self, rl: &RaylibHandle) -> Vector2 {
+        let mut pos = rl.get_mouse_position();
+        if let Some(width) = self.split_width {
+            pos.x = pos.x.rem_euclid(width);
+        }
+        pos
+    }
+}
+
+fn rect_from_corners(a: Vector2, b: Vector2) -> Rectangle {
+    Rectangle::new(
+        a.x.min(b.x),
+        a.y.min(b.y),
+        (a.x - b.x).abs(),
+        (a.y - b.y).abs(),
+    )
+}
+
+#[derive(Clone, Copy)]
+enum Param {
+    MutationRate,
+    GeneLen,
+    RocketCount,
+    RocketSpeed,
+}
+
+const PANEL_PARAMS: [Param; 4] = [
+    Param::MutationRate,
+    Param::GeneLen,
+    Param::RocketCount,
+    Param::RocketSpeed,
+];
+
+impl Param {
+    fn label(&self) -> &'static str {
+        match self {
+            Param::MutationRate => "Mutation rate",
+            Param::GeneLen => "Gene length",
+            Param::RocketCount => "Rocket count",
+            Param::RocketSpeed => "Rocket speed",
+        }
+    }
+
+    // Value as shown in the panel, along with the one waiting for the next generation
+    fn value(&self, world: &World) -> String {
+        let with_pending = |current: usize, pending: Option<usize>| match pending {
+            Some(pending) if pending != current => format!("{} -> {}", current, pending),
+            _ => current.to_string(),
+        };
+        match self {
+            Param::MutationRate => format!("{:.3}", world.config.mutation_rate),
+            Param::GeneLen => with_pending(world.config.gene_len, world.pending_gene_len),
+            Param::RocketCount => {
+                with_pending(world.config.rocket_count, world.pending_rocket_count)
+            }
+            Param::RocketSpeed => format!("{:.1}", world.config.rocket_speed),
+        }
+    }
+
+    // Moves the parameter one step up or down, `steps` is +1 or -1
+    fn adjust(&self, world: &mut World, steps: i32) {
+        let steps_f32 = steps as f32;
+        match self {
+            Param::MutationRate => {
+                world.config.mutation_rate =
+                    (world.config.mutation_rate + 0.005 * steps_f32).clamp(0.0, 1.0);
+            }
+            Param::GeneLen => {
+                let current = world.pending_gene_len.unwrap_or(world.config.gene_len);
+                let mut gene_len = (current as i32 + 50 * steps).max(50) as usize;
+                // Has to stay inside the range genome lengths mutate within
+                if let Some(length) = world.config.length_mutation {
+                    gene_len = gene_len.clamp(length.min_len, length.max_len);
+                }
+                world.pending_gene_len = Some(gene_len);
+            }
+            Param::RocketCount => {
+                let current = world
+                    .pending_rocket_count
+                    .unwrap_or(world.config.rocket_count);
+                world.pending_rocket_count = Some((current as i32 + 10 * steps).max(10) as usize);
+            }
+            Param::RocketSpeed => {
+                world.config.rocket_speed = (world.config.rocket_speed + 0.5 * steps_f32).max(0.5);
+            }
+        }
+    }
+}
+
+// Panel sits under the fitness graph, returns the row and its (-, +) buttons
+fn panel_row_rects(screen_width: f32, row: usize) -> (Rectangle, Rectangle, Rectangle) {
+    let x = screen_width - PANEL_WIDTH - GRAPH_MARGIN;
+    let y = GRAPH_MARGIN * 2.0 + GRAPH_SIZE.y + row as f32 * PANEL_ROW_HEIGHT;
+    let button_y = y + (PANEL_ROW_HEIGHT - PANEL_BUTTON_SIZE) / 2.0;
+    let plus_x = x + PANEL_WIDTH - PANEL_BUTTON_SIZE - 5.0;
+    (
+        Rectangle::new(x, y, PANEL_WIDTH, PANEL_ROW_HEIGHT),
+        Rectangle::new(
+            plus_x - PANEL_BUTTON_SIZE - 5.0,
+            button_y,
+            PANEL_BUTTON_SIZE,
+            PANEL_BUTTON_SIZE,
+        ),
+        Rectangle::new(plus_x, button_y, PANEL_BUTTON_SIZE, PANEL_BUTTON_SIZE),
+    )
+}
+
+enum Actions {
+    Pause,
+    Reset,
+    KillAll,
+    ToggleTrails,
+    ToggleGraph,
+    ToggleHeatmap,
+    ToggleDistances,
+    ToggleMinimap,
+    TogglePanel,
+    ToggleLeader,
+    ToggleLineage,
+    ToggleAgeTint,
+    ToggleTooltip,
+    ToggleLandscape,
+    ToggleTriangles,
+    ToggleTimerRing,
+    ToggleSpray,
+    CycleDeathOverlay,
+    ToggleLeaderboard,
+    ToggleFrozenPaths,
+    ToggleVictoryPause,
+    ToggleUncapped,
+    AdjustParam(Param, i32),
+    ToggleEditMode,
+    AddWall(Rectangle),
+    RemoveWall(Vector2),
+    MoveWall(usize, Vector2),
+    PlaceTarget(Vector2),
+    SaveBest,
+    SaveGeneration,
+    Screenshot,
+    HistoryBack,
+    HistoryForward,
+    LoadBest,
+    SpeedUp,
+    SlowDown,
+    Step,
+    Nothing,
+}
+
+fn handle_camera(rl: &RaylibHandle, view: &mut View) {
+    let mouse_pos = view.mouse_position(rl);
+    let wheel = rl.get_mouse_wheel_move();
+    if wheel != 0.0 {
+        // Zoom around the cursor so the point under it stays put
+        view.camera.target = rl.get_screen_to_world2D(mouse_pos, view.camera);
+        view.camera.offset = mouse_pos;
+        view.camera.zoom = (view.camera.zoom * (1.0 + wheel * ZOOM_STEP)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+    if rl.is_mouse_button_down(MouseButton::MOUSE_MIDDLE_BUTTON) {
+        if let Some(last) = view.pan_last {
+            view.camera.target -= (mouse_pos - last) / view.camera.zoom;
+        }
+        view.pan_last = Some(mouse_pos);
+    } else {
+        view.pan_last = None;
+    }
+}
+
+fn handle_input(rl: &RaylibHandle, view: &mut View, walls: &[Rectangle]) -> Actions {
+    handle_camera(rl, view);
+    if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+        return Actions::Pause;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_R) {
+        return Actions::Reset;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_K) {
+        return Actions::KillAll;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_T) {
+        return Actions::ToggleTrails;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_G) {
+        return Actions::ToggleGraph;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_H) {
+        return Actions::ToggleHeatmap;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_D) {
+        return Actions::ToggleDistances;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_M) {
+        return Actions::ToggleMinimap;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_P) {
+        return Actions::TogglePanel;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_B) {
+        return Actions::ToggleLeader;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_C) {
+        return Actions::ToggleLineage;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_U) {
+        return Actions::ToggleAgeTint;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_I) {
+        return Actions::ToggleTooltip;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_F) {
+        return Actions::ToggleLandscape;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_A) {
+        return Actions::ToggleTriangles;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_O) {
+        return Actions::ToggleTimerRing;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_X) {
+        return Actions::ToggleSpray;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_J) {
+        return Actions::CycleDeathOverlay;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_N) {
+        return Actions::ToggleLeaderboard;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_Q) {
+        return Actions::ToggleFrozenPaths;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_W) {
+        return Actions::ToggleVictoryPause;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_V) {
+        return Actions::ToggleUncapped;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_E) {
+        return Actions::ToggleEditMode;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_S) {
+        if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT)
+        {
+            return Actions::SaveGeneration;
+        }
+        return Actions::SaveBest;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_F2) {
+        return Actions::Screenshot;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) {
+        return Actions::HistoryBack;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) {
+        return Actions::HistoryForward;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_L) {
+        return Actions::LoadBest;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_EQUAL) || rl.is_key_pressed(KeyboardKey::KEY_KP_ADD) {
+        return Actions::SpeedUp;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_MINUS) || rl.is_key_pressed(KeyboardKey::KEY_KP_SUBTRACT)
+    {
+        return Actions::SlowDown;
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_PERIOD) || rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+        return Actions::Step;
+    }
+    // Clicks on the panel are used up by it, so they never reach the world below
+    if view.show_panel && rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+        let mouse_pos = view.mouse_position(rl);
+        let screen_width = view.split_width.unwrap_or(rl.get_screen_width() as f32);
+        for (row, &param) in PANEL_PARAMS.iter().enumerate() {
+            let (bounds, minus, plus) = panel_row_rects(screen_width, row);
+            if minus.check_collision_point_rec(mouse_pos) {
+                return Actions::AdjustParam(param, -1);
+            }
+            if plus.check_collision_point_rec(mouse_pos) {
+                return Actions::AdjustParam(param, 1);
+            }
+            if bounds.check_collision_point_rec(mouse_pos) {
+                return Actions::Nothing;
+            }
+        }
+    }
+    if view.edit_mode {
+        let mouse_pos = rl.get_screen_to_world2D(view.mouse_position(rl), view.camera);
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            view.drag_start = Some(mouse_pos);
+        }
+        if rl.is_mouse_button_released(MouseButton::MOUSE_LEFT_BUTTON) {
+            if let Some(start) = view.drag_start.take() {
+                let wall = rect_from_corners(start, mouse_pos);
+                if wall.width >= 1.0 && wall.height >= 1.0 {
+                    return Actions::AddWall(wall);
+                }
+            }
+        }
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) {
+            return Actions::RemoveWall(mouse_pos);
+        }
+    } else {
+        let mouse_pos = rl.get_screen_to_world2D(view.mouse_position(rl), view.camera);
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            // Grabbing a wall works paused or running, clicking anywhere else asks for the
+            // target, which `run_window` only moves while paused
+            match walls
+                .iter()
+                .rposition(|wall| wall.check_collision_point_rec(mouse_pos))
+            {
+                Some(ind) => {
+                    let corner = Vector2::new(walls[ind].x, walls[ind].y);
+                    view.grabbed_wall = Some((ind, corner - mouse_pos));
+                }
+                None => return Actions::PlaceTarget(mouse_pos),
+            }
+        }
+        if !rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+            view.grabbed_wall = None;
+        }
+        if let Some((ind, offset)) = view.grabbed_wall {
+            let mut corner = mouse_pos + offset;
+            if rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT)
+            {
+                corner.x = (corner.x / WALL_SNAP_SIZE).round() * WALL_SNAP_SIZE;
+                corner.y = (corner.y / WALL_SNAP_SIZE).round() * WALL_SNAP_SIZE;
+            }
+            return Actions::MoveWall(ind, corner);
+        }
+    }
+    Actions::Nothing
+}
+
+fn update(world: &mut World) {
+    // Purely cosmetic, the generation waits at the spawn until the fade is over. It only
+    // costs wall clock frames, nothing about the flight changes
+    if world.respawn_frames_left > 0 {
+        world.respawn_frames_left -= 1;
+        if world.respawn_frames_left == 0 || world.sim_speed > 1 {
+            world.end_respawn_fade();
+        }
+        return;
+    }
+    // No point flying out the rest of the genes once nothing is still alive
+    if world.frame_counter >= world.flight_len() || world.alive_count == 0 {
+        let start = Instant::now();
+        world.restart();
+        world.record_phase(|el| &mut el.restart, start);
+        return;
+    }
+    world.move_target();
+    world.refresh_landscape();
+    // Collision checks only read the world, so every rocket is checked in parallel
+    let collisions: Vec<Option<Collision>> = (0..world.rockets.len())
+        .into_par_iter()
+        .map(|ind| match world.rockets[ind].state {
+            RocketState::Alive => world.collision_rocket(ind),
+            // Rockets that are done stay put, even once a gate opens or the target moves away
+            RocketState::Dead | RocketState::Successful | RocketState::Timeout => None,
+        })
+        .collect();
+
+    for (ind, (rocket, collision)) in world.rockets.iter_mut().zip(collisions).enumerate() {
+        if rocket.state != RocketState::Alive {
+            continue;
+        }
+        match collision {
+            Some(Collision::Obstacle) => {
                 world.alive_count -= 1;
+                rocket.crash();
+                continue;
             }
-            rocket.state = RocketState::Dead;
+            Some(Collision::Wall(push)) => {
+                rocket.health -= world.config.wall_damage;
+                if rocket.health <= 0.0 {
+                    world.alive_count -= 1;
+                    rocket.crash();
+                    continue;
+                }
+                rocket.bounce(push);
+            }
+            Some(Collision::Target) if rocket.next_target + 1 < world.targets.len() => {
+                rocket.next_target += 1;
+            }
+            Some(Collision::Target) => {
+                world.alive_count -= 1;
+                rocket.frames_to_success = Some(world.frame_counter);
+                world.fastest_success =
+                    Some(world.fastest_success.map_or(world.frame_counter, |frames| {
+                        frames.min(world.frame_counter)
+                    }));
+                rocket.state = RocketState::Successful;
+                world.first_success.get_or_insert(ind);
+                continue;
+            }
+            None => {}
+        }
+        if rocket.fuel == 0 {
+            world.alive_count -= 1;
+            rocket.crash();
             continue;
         }
-        if succ_inds.contains(&ind) {
-            rocket.state = RocketState::Successful;
+        rocket.fuel -= 1;
+
+        let speed = world.config.rocket_speed;
+        let (max_turn_rate, momentum) = (world.config.max_turn_rate, world.config.angular_momentum);
+        // Wind or gravity drifts constant speed rockets and accelerates the rest
+        let environment = world.config.environment_force;
+        let pos_offset = match (rocket.dna.next_gene(), world.config.motion_model) {
+            (Some(Gene::Angle(gene)), MotionModel::ConstantSpeed) => {
+                rocket.angle += rocket.turn_rate(gene, max_turn_rate, momentum);
+                rocket.calc_offset(speed) + environment
+            }
+            (Some(Gene::Angle(gene)), MotionModel::Thrust) => {
+                let turn = rocket.turn_rate(gene, max_turn_rate, momentum);
+                let force = rocket.thrust_force(turn, world.config.thrust);
+                rocket.apply_force(force + environment, speed)
+            }
+            (Some(Gene::Force(force)), _) => rocket.apply_force(force + environment, speed),
+            // Out of genes, so the rocket coasts along its current heading
+            (None, MotionModel::ConstantSpeed) if matches!(rocket.dna.genes, Genes::Angle(_)) => {
+                rocket.calc_offset(speed) + environment
+            }
+            (None, _) => rocket.apply_force(environment, speed),
+        };
+        rocket.pos.x += pos_offset.x;
+        rocket.pos.y += pos_offset.y;
+        rocket.path_length += pos_offset.length();
+        rocket.apply_boundary(
+            world.boundary_mode,
+            world.config.screen_width as f32,
+            world.config.screen_height as f32,
+        );
+
+        rocket.path.push(rocket.pos);
+    }
+    world.calc_dist_from_target();
+    world.frame_counter += 1;
+    world.sim_frames += 1;
+    world.update_timer();
+}
+
+// Cursor and camera details `render_arena` needs from the handle, worked out once a frame
+struct FrameInput {
+    // Screen position of the cursor, folded into one side in a split screen
+    mouse_screen: Vector2,
+    mouse_pos: Vector2,
+    // Part of the world the camera currently shows
+    viewport: Rectangle,
+}
+
+// The first world of each arena owns the map, any others only add their rockets to it.
+// A split screen gives every world its own side, otherwise they all share one arena.
+// `screenshot` saves the finished frame to that path, `spray` is the long exposure canvas
+fn render(
+    mut ctx: RaylibDrawHandle,
+    worlds: &[World],
+    view: &View,
+    screenshot: Option<&str>,
+    spray: Option<&RenderTexture2D>,
+) {
+    ctx.clear_background(BACKGROUND_COLOR);
+    let mut mouse_screen = ctx.get_mouse_position();
+    let (width, height) = (
+        worlds[0].config.screen_width as f32,
+        worlds[0].config.screen_height as f32,
+    );
+    if let Some(split_width) = view.split_width {
+        mouse_screen.x = mouse_screen.x.rem_euclid(split_width);
+    }
+    let top_left = ctx.get_screen_to_world2D(Vector2::zero(), view.camera);
+    let bottom_right = ctx.get_screen_to_world2D(Vector2::new(width, height), view.camera);
+    let input = FrameInput {
+        mouse_screen,
+        mouse_pos: ctx.get_screen_to_world2D(mouse_screen, view.camera),
+        viewport: rect_from_corners(top_left, bottom_right),
+    };
+
+    match view.split_width {
+        Some(split_width) => {
+            for ind in 0..worlds.len() {
+                let x = ind as f32 * split_width;
+                let mut side =
+                    ctx.begin_scissor_mode(x as i32, 0, split_width as i32, height as i32);
+                render_arena(
+                    &mut side,
+                    worlds,
+                    ind..ind + 1,
+                    view,
+                    Vector2::new(x, 0.0),
+                    None,
+                    &input,
+                );
+            }
+            for ind in 1..worlds.len() {
+                let x = ind as f32 * split_width;
+                ctx.draw_line_ex(
+                    Vector2::new(x, 0.0),
+                    Vector2::new(x, height),
+                    2.0,
+                    WALL_COLOR,
+                );
+            }
+        }
+        None => render_arena(
+            &mut ctx,
+            worlds,
+            0..worlds.len(),
+            view,
+            Vector2::zero(),
+            spray,
+            &input,
+        ),
+    }
+    if view.uncapped {
+        let label = format!("{} FPS (uncapped)", ctx.get_fps());
+        ctx.draw_text(label.as_str(), 15, 15, 20, Color::LIME);
+    } else {
+        ctx.draw_fps(15, 15);
+    }
+
+    if let Some(path) = screenshot {
+        // Ending a 2D mode flushes everything batched so far, the pixels have to be read
+        // before the handle drops and the frame gets swapped out
+        drop(ctx.begin_mode2D(Camera2D {
+            offset: Vector2::zero(),
+            target: Vector2::zero(),
+            rotation: 0.0,
+            zoom: 1.0,
+        }));
+        // The safe wrapper needs the handle the frame is borrowing
+        let c_path = CString::new(path).unwrap();
+        unsafe { raylib::ffi::TakeScreenshot(c_path.as_ptr()) };
+        println!("Saved screenshot to {}", path);
+    }
+}
+
+// Draws the worlds in `arena` as one scene, moved over by `shift` on the screen
+fn render_arena(
+    ctx: &mut impl RaylibDraw,
+    worlds: &[World],
+    arena: Range<usize>,
+    view: &View,
+    shift: Vector2,
+    spray: Option<&RenderTexture2D>,
+    input: &FrameInput,
+) {
+    let victory = view
+        .victory
+        .filter(|(world_ind, _, _)| arena.contains(world_ind))
+        .map(|(world_ind, rocket_ind, time_left)| {
+            (&worlds[world_ind].rockets[rocket_ind], time_left)
+        });
+    let worlds = &worlds[arena];
+    let world = &worlds[0];
+    let mut camera = view.camera;
+    camera.offset += shift;
+    render_scene(
+        &mut ctx.begin_mode2D(camera),
+        worlds,
+        view,
+        input.mouse_pos,
+        spray,
+        victory,
+    );
+
+    // Everything below stays in screen space, only moved over onto the arena's side
+    let mut ctx = ctx.begin_mode2D(Camera2D {
+        offset: shift,
+        target: Vector2::zero(),
+        rotation: 0.0,
+        zoom: 1.0,
+    });
+    let ctx = &mut ctx;
+    if !view.timer_ring {
+        ctx.draw_rectangle_rec(world.timer_rect, TIMER_RECT_COLOR);
+    }
+
+    let mut label = format!(
+        "Generation {}   Speed {}x",
+        world.generation, world.sim_speed
+    );
+    // The sides can't be told apart by color, so each one gets its best fitness
+    if view.split_width.is_some() {
+        label += format!("   Best {:.4}", world.best_fitness_ever).as_str();
+    }
+
+    ctx.draw_text(
+        label.as_str(),
+        20,
+        world.config.screen_height - 40,
+        20,
+        Color::RAYWHITE,
+    );
+
+    if view.edit_mode {
+        let label = "EDIT MODE: drag to add a wall, right click to remove one";
+        let x = (world.config.screen_width - measure_text(label, 20)) / 2;
+        ctx.draw_text(
+            label,
+            x,
+            world.config.screen_height - 40,
+            20,
+            Color::RAYWHITE,
+        );
+    }
+
+    if let Some(generation) = world.solved_generation {
+        let label = format!("SOLVED in generation {}", generation);
+        let x =
+            (world.config.screen_width - measure_text(label.as_str(), SOLVED_BANNER_TEXT_SIZE)) / 2;
+        ctx.draw_text(
+            label.as_str(),
+            x,
+            15,
+            SOLVED_BANNER_TEXT_SIZE,
+            SUCCESSFUL_ROCKET_COLOR,
+        );
+    }
+
+    let hud_bottom = render_hud(ctx, world);
+    if worlds.len() > 1 {
+        render_race(ctx, worlds, hud_bottom);
+    }
+    if view.show_graph {
+        render_fitness_graph(ctx, world);
+    }
+    if view.show_heatmap {
+        render_gene_heatmap(ctx, world);
+    }
+    if view.show_panel {
+        render_panel(ctx, world);
+    }
+    if view.show_leaderboard {
+        render_leaderboard(ctx, world);
+    }
+    if view.show_tooltip {
+        if let Some(rocket) = hovered_rocket(worlds, input.mouse_pos) {
+            render_tooltip(ctx, rocket, input.mouse_screen);
+        }
+    }
+    if view.show_minimap {
+        render_minimap(ctx, worlds, input.viewport);
+    }
+}
+
+// Whole map scaled down into the bottom right corner, `viewport` is the part of the
+// world the camera currently shows
+fn render_minimap(ctx: &mut impl RaylibDraw, worlds: &[World], viewport: Rectangle) {
+    let world = &worlds[0];
+    let (width, height) = (
+        world.config.screen_width as f32,
+        world.config.screen_height as f32,
+    );
+    let scale = MINIMAP_WIDTH / width;
+    let bounds = Rectangle::new(
+        width - MINIMAP_WIDTH - MINIMAP_MARGIN,
+        height - height * scale - MINIMAP_MARGIN - (TIMER_RECT_HEIGHT + HEATMAP_HEIGHT) as f32,
+        MINIMAP_WIDTH,
+        height * scale,
+    );
+    let to_minimap =
+        |pos: Vector2| Vector2::new(bounds.x + pos.x * scale, bounds.y + pos.y * scale);
+    let scale_rect = |rect: Rectangle| {
+        let corner = to_minimap(Vector2::new(rect.x, rect.y));
+        Rectangle::new(corner.x, corner.y, rect.width * scale, rect.height * scale)
+    };
+
+    ctx.draw_rectangle_rec(bounds, GRAPH_BACKGROUND_COLOR);
+    for wall in &world.walls {
+        ctx.draw_rectangle_rec(scale_rect(*wall), WALL_COLOR);
+    }
+    for polygon in &world.polygon_walls {
+        let points: Vec<Vector2> = polygon.iter().map(|&el| to_minimap(el)).collect();
+        ctx.draw_triangle_fan(&points, WALL_COLOR);
+    }
+    for &(center, radius) in &world.circle_obstacles {
+        ctx.draw_circle_v(to_minimap(center), radius * scale, WALL_COLOR);
+    }
+    for target in &world.targets {
+        ctx.draw_circle_v(
+            to_minimap(*target),
+            world.config.target_radius * scale,
+            TARGET_INNER_COLOR,
+        );
+    }
+    for world in worlds {
+        for rocket in &world.rockets {
+            let color = rocket_color(rocket.state, world.species_color);
+            ctx.draw_pixel_v(to_minimap(rocket.pos), color);
+        }
+    }
+    ctx.draw_rectangle_lines_ex(bounds, 1, WALL_COLOR);
+    ctx.draw_rectangle_lines_ex(scale_rect(viewport), 1, MINIMAP_VIEWPORT_COLOR);
+}
+
+// Strip above the timer bar, one column per gene colored by its variance
+fn render_gene_heatmap(ctx: &mut impl RaylibDraw, world: &World) {
+    let variance = &world.gene_variance;
+    let max = variance.iter().copied().fold(0.0, f32::max);
+    if max <= f32::EPSILON {
+        return;
+    }
+    let width = world.config.screen_width as f32 / variance.len() as f32;
+    let y = (world.config.screen_height - TIMER_RECT_HEIGHT - HEATMAP_HEIGHT) as f32;
+    for (ind, &value) in variance.iter().enumerate() {
+        let color = lerp_color(HEATMAP_LOW_COLOR, HEATMAP_HIGH_COLOR, value / max);
+        ctx.draw_rectangle_rec(
+            Rectangle::new(ind as f32 * width, y, width.ceil(), HEATMAP_HEIGHT as f32),
+            color,
+        );
+    }
+}
+
+fn render_death_crosses(ctx: &mut impl RaylibDraw, world: &World) {
+    for pos in world.rockets.iter().filter_map(|el| el.death_pos) {
+        let (a, b) = (
+            Vector2::new(DEATH_CROSS_SIZE, DEATH_CROSS_SIZE),
+            Vector2::new(DEATH_CROSS_SIZE, -DEATH_CROSS_SIZE),
+        );
+        ctx.draw_line_v(pos - a, pos + a, DEATH_COLOR);
+        ctx.draw_line_v(pos - b, pos + b, DEATH_COLOR);
+    }
+}
+
+// Counts the deaths of the finished generations in `death_history` and the current one
+fn render_death_density(ctx: &mut impl RaylibDraw, world: &World) {
+    let cols = (world.config.screen_width as f32 / DEATH_CELL_SIZE).ceil() as usize;
+    let rows = (world.config.screen_height as f32 / DEATH_CELL_SIZE).ceil() as usize;
+    let mut counts = vec![0u32; cols * rows];
+    let current = world.rockets.iter().filter_map(|el| el.death_pos);
+    for pos in world.death_history.iter().flatten().copied().chain(current) {
+        let col = ((pos.x / DEATH_CELL_SIZE).max(0.0) as usize).min(cols - 1);
+        let row = ((pos.y / DEATH_CELL_SIZE).max(0.0) as usize).min(rows - 1);
+        counts[row * cols + col] += 1;
+    }
+    let max = counts.iter().copied().max().unwrap_or(0);
+    if max == 0 {
+        return;
+    }
+    for (ind, &count) in counts.iter().enumerate() {
+        if count == 0 {
             continue;
         }
-        rocket.angle += rocket.dna.next_angle();
+        let mut color = DEATH_COLOR;
+        color.a = (count as f32 / max as f32 * DEATH_DENSITY_MAX_ALPHA) as u8;
+        ctx.draw_rectangle_rec(
+            Rectangle::new(
+                (ind % cols) as f32 * DEATH_CELL_SIZE,
+                (ind / cols) as f32 * DEATH_CELL_SIZE,
+                DEATH_CELL_SIZE,
+                DEATH_CELL_SIZE,
+            ),
+            color,
+        );
+    }
+}
+
+// Older generations' winning flights fade out so the route can be seen refining over time
+fn render_frozen_paths(ctx: &mut impl RaylibDraw, world: &World) {
+    let count = world.frozen_paths.len();
+    for (ind, (_, path)) in world.frozen_paths.iter().enumerate() {
+        let t = (ind + 1) as f32 / count as f32;
+        let mut color = FROZEN_PATH_COLOR;
+        color.a = (FROZEN_PATH_MIN_ALPHA as f32
+            + (FROZEN_PATH_COLOR.a - FROZEN_PATH_MIN_ALPHA) as f32 * t) as u8;
+        ctx.draw_line_strip(path, color);
+    }
+    if let Some((generation, path)) = world.frozen_paths.back() {
+        if let Some(&end) = path.last() {
+            ctx.draw_text(
+                format!("gen {}", generation).as_str(),
+                end.x as i32 + 8,
+                end.y as i32 - 8,
+                TOOLTIP_TEXT_SIZE,
+                FROZEN_PATH_COLOR,
+            );
+        }
+    }
+}
+
+// Green where the fitness function pulls rockets to, blue where it pushes them away from
+fn render_landscape(ctx: &mut impl RaylibDraw, world: &World) {
+    let (cols, _) = world.landscape_size();
+    for (ind, &value) in world.landscape.iter().enumerate() {
+        let (col, row) = (ind % cols, ind / cols);
+        ctx.draw_rectangle_rec(
+            Rectangle::new(
+                col as f32 * LANDSCAPE_CELL_SIZE,
+                row as f32 * LANDSCAPE_CELL_SIZE,
+                LANDSCAPE_CELL_SIZE,
+                LANDSCAPE_CELL_SIZE,
+            ),
+            lerp_color(LANDSCAPE_NEAR_COLOR, LANDSCAPE_FAR_COLOR, value),
+        );
+    }
+}
+
+// Draws everything that lives in world coordinates, `mouse_pos` is the cursor in world space
+// and `victory` the rocket the victory pause flashes with the seconds it has left
+fn render_scene(
+    ctx: &mut impl RaylibDraw,
+    worlds: &[World],
+    view: &View,
+    mouse_pos: Vector2,
+    spray: Option<&RenderTexture2D>,
+    victory: Option<(&Rocket, f32)>,
+) {
+    let world = &worlds[0];
+    if view.show_landscape {
+        render_landscape(ctx, world);
+    }
+    if let Some(canvas) = spray {
+        // Render textures come out upside down, so the source is flipped back
+        let (width, height) = (canvas.texture().width, canvas.texture().height);
+        ctx.draw_texture_rec(
+            canvas.texture(),
+            Rectangle::new(0.0, 0.0, width as f32, -height as f32),
+            Vector2::zero(),
+            Color::WHITE,
+        );
+    }
+
+    // Draw the previous generation's best flight under everything else
+    ctx.draw_line_strip(&world.ghost_path, GHOST_PATH_COLOR);
+    if view.show_frozen_paths {
+        render_frozen_paths(ctx, world);
+    }
+
+    for world in worlds {
+        render_rockets(ctx, world, view);
+    }
+
+    // Blink the rocket that set off the victory pause
+    if let Some((rocket, time_left)) = victory {
+        if (time_left * VICTORY_FLASH_RATE) as i32 % 2 == 0 {
+            draw_rocket_body(ctx, rocket, VICTORY_FLASH_COLOR, view.triangle_rockets);
+            ctx.draw_ring(
+                rocket.pos,
+                LEADER_RING_RADIUS - 2.0,
+                LEADER_RING_RADIUS,
+                0.0,
+                360.0,
+                36,
+                VICTORY_FLASH_COLOR,
+            );
+        }
+    }
+
+    // Draw walls
+    for wall in &world.walls {
+        ctx.draw_rectangle_rec(wall, WALL_COLOR);
+    }
+    for polygon in &world.polygon_walls {
+        ctx.draw_triangle_fan(polygon, WALL_COLOR);
+    }
+    for &(center, radius) in &world.circle_obstacles {
+        ctx.draw_circle_v(center, radius, WALL_COLOR);
+    }
+    for gate in &world.gates {
+        let color = if gate.is_open(world.frame_counter) {
+            GATE_OPEN_COLOR
+        } else {
+            GATE_CLOSED_COLOR
+        };
+        ctx.draw_rectangle_rec(gate.rect, color);
+    }
+    match view.death_overlay {
+        DeathOverlay::Off => {}
+        DeathOverlay::Crosses => render_death_crosses(ctx, world),
+        DeathOverlay::Density => render_death_density(ctx, world),
+    }
+    if let Some(start) = view.drag_start {
+        let preview = rect_from_corners(start, mouse_pos);
+        ctx.draw_rectangle_rec(preview, WALL_PREVIEW_COLOR);
+    }
+
+    // Draw targets, dimming the ones that some rocket has already passed
+    let rockets = worlds.iter().flat_map(|el| &el.rockets);
+    let reached_count = if rockets
+        .clone()
+        .any(|el| el.state == RocketState::Successful)
+    {
+        world.targets.len()
+    } else {
+        rockets.map(|el| el.next_target).max().unwrap_or(0)
+    };
+    let radius = world.config.target_radius;
+    let pulse = (world.run_time * TARGET_PULSE_RATE).fract();
+    for (ind, target) in world.targets.iter().enumerate() {
+        let (mut outer_color, mut inner_color) = (TARGET_OUTER_COLOR, TARGET_INNER_COLOR);
+        let reached = world.targets.len() > 1 && ind < reached_count;
+        if reached {
+            outer_color.a = REACHED_TARGET_ALPHA;
+            inner_color.a = REACHED_TARGET_ALPHA;
+        }
+        ctx.draw_circle_v(target, radius, outer_color);
+        ctx.draw_circle_v(target, radius / 2.0, inner_color);
+        if !reached {
+            let pulse_radius = radius + pulse * TARGET_PULSE_SPREAD;
+            let mut pulse_color = TARGET_OUTER_COLOR;
+            pulse_color.a = (TARGET_PULSE_ALPHA as f32 * (1.0 - pulse)) as u8;
+            ctx.draw_ring(
+                target,
+                pulse_radius,
+                pulse_radius + 2.0,
+                0.0,
+                360.0,
+                36,
+                pulse_color,
+            );
+        }
+    }
+    if let (true, Some(&target)) = (view.timer_ring, world.targets.last()) {
+        // An angle of 180 points straight up, the ring empties towards it
+        let inner_radius = radius + TIMER_RING_GAP;
+        ctx.draw_ring(
+            target,
+            inner_radius,
+            inner_radius + TIMER_RING_WIDTH,
+            180.0,
+            180.0 + 360.0 * world.time_left(),
+            36,
+            TIMER_RECT_COLOR,
+        );
+    }
+}
+
+// Alive rockets show `base_color` as is, dead ones fade it and successful ones stand out
+fn rocket_color(state: RocketState, base_color: Color) -> Color {
+    match state {
+        RocketState::Dead => Color::new(
+            base_color.r,
+            base_color.g,
+            base_color.b,
+            DEAD_ROCKET_COLOR.a,
+        ),
+        RocketState::Alive => base_color,
+        RocketState::Successful => SUCCESSFUL_ROCKET_COLOR,
+        RocketState::Timeout => TIMEOUT_ROCKET_COLOR,
+    }
+}
+
+// `triangle` draws the body as a triangle pointing where the rocket is heading
+fn draw_rocket_body(ctx: &mut impl RaylibDraw, rocket: &Rocket, color: Color, triangle: bool) {
+    if !triangle {
+        ctx.draw_rectangle_pro(
+            Rectangle::new(rocket.pos.x, rocket.pos.y, ROCKET_SIZE.x, ROCKET_SIZE.y),
+            Vector2::new(ROCKET_SIZE.x / 2.0, ROCKET_SIZE.y / 2.0),
+            rocket.angle + 90.0,
+            color,
+        );
+        return;
+    }
+    let angle = rocket.angle.to_radians();
+    let forward = Vector2::new(angle.cos(), angle.sin());
+    let side = Vector2::new(-forward.y, forward.x);
+    let tip = rocket.pos + forward * (ROCKET_SIZE.y / 2.0);
+    let back = rocket.pos - forward * (ROCKET_SIZE.y / 2.0);
+    let (left, right) = (
+        back - side * (ROCKET_SIZE.x / 2.0),
+        back + side * (ROCKET_SIZE.x / 2.0),
+    );
+    // raylib only fills triangles given counter-clockwise as seen on screen
+    let (to_left, to_right) = (left - tip, right - tip);
+    if to_left.x * to_right.y - to_left.y * to_right.x < 0.0 {
+        ctx.draw_triangle(tip, left, right, color);
+    } else {
+        ctx.draw_triangle(tip, right, left, color);
+    }
+}
+
+// Fades the long exposure canvas a little and adds a dot under every rocket still flying,
+// the canvas covers the whole arena so it pans and zooms along with everything else
+fn render_spray(ctx: &mut impl RaylibDraw, worlds: &[World], view: &View) {
+    let world = &worlds[0];
+    let mut fade_color = BACKGROUND_COLOR;
+    fade_color.a = SPRAY_FADE_ALPHA;
+    ctx.draw_rectangle(
+        0,
+        0,
+        world.config.screen_width,
+        world.config.screen_height,
+        fade_color,
+    );
+    for world in worlds {
+        for rocket in &world.rockets {
+            if rocket.state != RocketState::Alive {
+                continue;
+            }
+            let mut color = if view.show_lineage {
+                rocket.dna.lineage_color()
+            } else {
+                world.species_color
+            };
+            color.a = SPRAY_DOT_ALPHA;
+            ctx.draw_circle_v(rocket.pos, SPRAY_DOT_RADIUS, color);
+        }
+    }
+}
+
+// Trails, distance lines and bodies of one population's rockets
+fn render_rockets(ctx: &mut impl RaylibDraw, world: &World, view: &View) {
+    let base_color = |rocket: &Rocket| {
+        if view.show_lineage {
+            rocket.dna.lineage_color()
+        } else {
+            world.species_color
+        }
+    };
+
+    // Draw trails, fading out towards the oldest point
+    if view.show_trails {
+        for rocket in &world.rockets {
+            let trail_color = match rocket.state {
+                RocketState::Successful => SUCCESSFUL_ROCKET_COLOR,
+                _ => base_color(rocket),
+            };
+            let trail = &rocket.path[rocket.path.len().saturating_sub(TRAIL_LEN)..];
+            for (ind, points) in trail.windows(2).enumerate() {
+                let alpha = (ind + 1) as f32 / TRAIL_LEN as f32 * 150.0;
+                let color = Color::new(trail_color.r, trail_color.g, trail_color.b, alpha as u8);
+                ctx.draw_line_v(points[0], points[1], color);
+            }
+        }
+    }
+
+    // Draw a line from every rocket still flying to the target it's heading for
+    if view.show_distances {
+        let (width, height) = (
+            world.config.screen_width as f32,
+            world.config.screen_height as f32,
+        );
+        let max_dist = (width.powi(2) + height.powi(2)).sqrt();
+        for rocket in &world.rockets {
+            if rocket.state == RocketState::Successful {
+                continue;
+            }
+            let t = (rocket.dist_from_target / max_dist).min(1.0);
+            let color = lerp_color(DIST_LINE_NEAR_COLOR, DIST_LINE_FAR_COLOR, t);
+            ctx.draw_line_v(rocket.pos, world.targets[rocket.next_target], color);
+        }
+    }
+
+    // Draw rockets, the new generation fades in while the last one fades out
+    let fade_out = world.respawn_frames_left as f32 / RESPAWN_FRAMES as f32;
+    let faded = |color: Color, amount: f32| {
+        Color::new(color.r, color.g, color.b, (color.a as f32 * amount) as u8)
+    };
+    for rocket in &world.fading_rockets {
+        let color = rocket_color(rocket.state, base_color(rocket));
+        draw_rocket_body(ctx, rocket, faded(color, fade_out), view.triangle_rockets);
+    }
+    for rocket in &world.rockets {
+        let mut color = rocket_color(rocket.state, base_color(rocket));
+        // Dead and successful rockets keep their own colors
+        if view.age_tint && rocket.state == RocketState::Alive {
+            let age = rocket.dna.curr_gene as f32 / rocket.dna.genes.len() as f32;
+            color = lerp_color(color, AGE_TINT_COLOR, age.min(1.0) * AGE_TINT_MAX);
+        }
+        draw_rocket_body(
+            ctx,
+            rocket,
+            faded(color, 1.0 - fade_out),
+            view.triangle_rockets,
+        );
+    }
+
+    if view.show_leader {
+        if let Some(rocket) = world.leading_rocket() {
+            ctx.draw_ring(
+                rocket.pos,
+                LEADER_RING_RADIUS - 2.0,
+                LEADER_RING_RADIUS,
+                0.0,
+                360.0,
+                36,
+                LEADER_RING_COLOR,
+            );
+        }
+    }
+}
+
+// One line per species from `top` down, plus which one is ahead on last generation's best
+// fitness
+fn render_race(ctx: &mut impl RaylibDraw, worlds: &[World], top: i32) {
+    let last_best = |world: &World| world.best_fitness_history.last().copied();
+    let leader = worlds
+        .iter()
+        .enumerate()
+        .filter_map(|(ind, world)| last_best(world).map(|fitness| (ind, fitness)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(ind, _)| ind);
+
+    for (ind, world) in worlds.iter().enumerate() {
+        let best = match last_best(world) {
+            Some(fitness) => format!("{:.4}", fitness),
+            None => "-".to_string(),
+        };
+        let solved = match world.first_solved_generation {
+            Some(generation) => format!("solved in gen {}", generation),
+            None => "unsolved".to_string(),
+        };
+        let marker = if leader == Some(ind) { "> " } else { "  " };
+        let text = format!(
+            "{}Species {} ({:?}): best {}, {}",
+            marker,
+            ind + 1,
+            world.selection_strategy,
+            best,
+            solved
+        );
+        let y = top + ind as i32 * HUD_LINE_HEIGHT;
+        ctx.draw_text(text.as_str(), 15, y, HUD_TEXT_SIZE, world.species_color);
+    }
+}
+
+// Returns the y under the last line it drew, which depends on the config
+fn render_hud(ctx: &mut impl RaylibDraw, world: &World) -> i32 {
+    // Rockets only time out as the generation ends, so there are never any to show
+    let (alive, dead, successful, _) = world.state_counts();
+    let closest = match world.closest_dist_to_target() {
+        Some(dist) => format!("Closest: {:.0} px", dist),
+        None => "Closest: -".to_string(),
+    };
+    let last_best = match world.best_fitness_history.last() {
+        Some(fitness) => format!("Last best fitness: {:.4}", fitness),
+        None => "Last best fitness: -".to_string(),
+    };
+    let diversity = match world.diversity {
+        Some(diversity) => format!("Diversity: {:.3}", diversity),
+        None => "Diversity: -".to_string(),
+    };
+    let generation_rate = if world.run_time > 0.0 {
+        world.generation as f32 / world.run_time
+    } else {
+        0.0
+    };
+    let mut lines = vec![
+        (format!("Alive: {}", alive), ALIVE_ROCKET_COLOR),
+        (format!("Dead: {}", dead), DEAD_ROCKET_COLOR),
+        (
+            format!("Successful: {}", successful),
+            SUCCESSFUL_ROCKET_COLOR,
+        ),
+        (closest, Color::RAYWHITE),
+        (last_best, Color::RAYWHITE),
+        (diversity, Color::RAYWHITE),
+        (format!("Sim frames: {}", world.sim_frames), Color::RAYWHITE),
+        (
+            format!(
+                "Run time: {:.1} s ({:.2} gen/s)",
+                world.run_time, generation_rate
+            ),
+            Color::RAYWHITE,
+        ),
+    ];
+    if !matches!(
+        world.config.population_schedule,
+        PopulationSchedule::Constant
+    ) {
+        lines.push((
+            format!("Population: {}", world.rockets.len()),
+            Color::RAYWHITE,
+        ));
+    }
+    if world.config.difficulty_ramp.is_some() {
+        let last = if world.next_ramp_target().is_none() {
+            " (last)"
+        } else {
+            ""
+        };
+        lines.push((
+            format!("Difficulty: level {}{}", world.ramp_solves.len(), last),
+            Color::RAYWHITE,
+        ));
+    }
+    for (ind, (text, color)) in lines.iter().enumerate() {
+        let y = 40 + ind as i32 * HUD_LINE_HEIGHT;
+        ctx.draw_text(text.as_str(), 15, y, HUD_TEXT_SIZE, color);
+    }
+
+    // Arrow pointing the way the environment pushes, on one more line under the others
+    let mut bottom = 40 + lines.len() as i32 * HUD_LINE_HEIGHT;
+    let force = world.config.environment_force;
+    if force.length() > f32::EPSILON {
+        let y = bottom;
+        bottom += HUD_LINE_HEIGHT;
+        let text = format!("Wind: {:.2}", force.length());
+        ctx.draw_text(text.as_str(), 15, y, HUD_TEXT_SIZE, Color::RAYWHITE);
+        let center = Vector2::new(
+            (25 + measure_text(text.as_str(), HUD_TEXT_SIZE)) as f32 + WIND_ARROW_LENGTH / 2.0,
+            (y + HUD_TEXT_SIZE / 2) as f32,
+        );
+        let dir = force.normalized();
+        let tip = center + dir * (WIND_ARROW_LENGTH / 2.0);
+        let normal = Vector2::new(-dir.y, dir.x) * (WIND_ARROW_HEAD / 2.0);
+        let head_base = tip - dir * WIND_ARROW_HEAD;
+        ctx.draw_line_ex(
+            center - dir * (WIND_ARROW_LENGTH / 2.0),
+            head_base,
+            2.0,
+            Color::RAYWHITE,
+        );
+        // Counter clockwise on screen whichever way the arrow points
+        ctx.draw_triangle(tip, head_base - normal, head_base + normal, Color::RAYWHITE);
+    }
+    bottom
+}
+
+// Rocket whose body is under `pos`, the one closest to it when several overlap
+fn hovered_rocket(worlds: &[World], pos: Vector2) -> Option<&Rocket> {
+    worlds
+        .iter()
+        .flat_map(|el| &el.rockets)
+        .filter(|el| el.bounding_box().check_collision_point_rec(pos))
+        .min_by(|a, b| {
+            let dist_a = a.pos.sub(pos).length();
+            let dist_b = b.pos.sub(pos).length();
+            dist_a.total_cmp(&dist_b)
+        })
+}
 
-        let pos_offset = rocket.calc_offset();
-        rocket.pos.x += pos_offset.x;
-        rocket.pos.y += pos_offset.y;
+// Lists the state and the next few genes of `rocket` next to the cursor
+fn render_tooltip(ctx: &mut impl RaylibDraw, rocket: &Rocket, screen_pos: Vector2) {
+    let dna = &rocket.dna;
+    let mut lines = vec![
+        format!("{:?}", rocket.state),
+        format!("Gene {} / {}", dna.curr_gene, dna.genes.len()),
+        format!("Fitness: {:.4}", dna.fitness),
+    ];
+    let upcoming = dna.curr_gene..(dna.curr_gene + TOOLTIP_GENE_COUNT).min(dna.genes.len());
+    for ind in upcoming {
+        let gene = match &dna.genes {
+            Genes::Angle(genes) => format!("{:+.2} deg", genes[ind]),
+            Genes::Force(genes) => format!("({:+.2}, {:+.2})", genes[ind].x, genes[ind].y),
+        };
+        lines.push(format!("  [{}] {}", ind, gene));
+    }
+
+    let width = lines
+        .iter()
+        .map(|el| measure_text(el.as_str(), TOOLTIP_TEXT_SIZE))
+        .max()
+        .unwrap_or(0)
+        + 10;
+    let height = lines.len() as i32 * TOOLTIP_LINE_HEIGHT + 8;
+    let (x, y) = (screen_pos.x as i32 + 15, screen_pos.y as i32 + 15);
+    ctx.draw_rectangle(x, y, width, height, GRAPH_BACKGROUND_COLOR);
+    ctx.draw_rectangle_lines(x, y, width, height, WALL_COLOR);
+    for (ind, text) in lines.iter().enumerate() {
+        ctx.draw_text(
+            text.as_str(),
+            x + 5,
+            y + 4 + ind as i32 * TOOLTIP_LINE_HEIGHT,
+            TOOLTIP_TEXT_SIZE,
+            Color::RAYWHITE,
+        );
     }
-    world.frame_counter += 1;
-    world.timer_rect.width -= SCREEN_WIDTH as f32 / GENE_LEN as f32;
 }
 
-fn render(mut ctx: RaylibDrawHandle, world: &World) {
-    ctx.clear_background(BACKGROUND_COLOR);
-    ctx.draw_fps(15, 15);
+// Bottom left, just above the generation counter
+fn render_leaderboard(ctx: &mut impl RaylibDraw, world: &World) {
+    let mut lines = vec!["#   fitness   state   distance".to_string()];
+    for (ind, fitness) in world.leaderboard() {
+        let rocket = &world.rockets[ind];
+        lines.push(format!(
+            "{:<3} {:.4}   {:?}   {:.0} px",
+            ind, fitness, rocket.state, rocket.dist_from_target
+        ));
+    }
 
-    // Draw rockets
-    for rocket in &world.rockets {
-        let rocket_color = match rocket.state {
-            RocketState::Dead => DEAD_ROCKET_COLOR,
-            RocketState::Alive => ALIVE_ROCKET_COLOR,
-            RocketState::Successful => SUCCESSFUL_ROCKET_COLOR,
-        };
-        ctx.draw_rectangle_pro(
-            Rectangle::new(rocket.pos.x, rocket.pos.y, ROCKET_SIZE.x, ROCKET_SIZE.y),
-            Vector2::new(ROCKET_SIZE.x / 2.0, ROCKET_SIZE.y / 2.0),
-            rocket.angle + 90.0,
-            rocket_color,
+    let width = lines
+        .iter()
+        .map(|el| measure_text(el.as_str(), TOOLTIP_TEXT_SIZE))
+        .max()
+        .unwrap_or(0)
+        + 10;
+    let height = lines.len() as i32 * TOOLTIP_LINE_HEIGHT + 8;
+    let (x, y) = (15, world.config.screen_height - 50 - height);
+    ctx.draw_rectangle(x, y, width, height, GRAPH_BACKGROUND_COLOR);
+    ctx.draw_rectangle_lines(x, y, width, height, WALL_COLOR);
+    for (ind, text) in lines.iter().enumerate() {
+        ctx.draw_text(
+            text.as_str(),
+            x + 5,
+            y + 4 + ind as i32 * TOOLTIP_LINE_HEIGHT,
+            TOOLTIP_TEXT_SIZE,
+            Color::RAYWHITE,
+        );
+    }
+}
+
+fn render_panel(ctx: &mut impl RaylibDraw, world: &World) {
+    let screen_width = world.config.screen_width as f32;
+    for (row, param) in PANEL_PARAMS.iter().enumerate() {
+        let (bounds, minus, plus) = panel_row_rects(screen_width, row);
+        ctx.draw_rectangle_rec(bounds, GRAPH_BACKGROUND_COLOR);
+        let text = format!("{}: {}", param.label(), param.value(world));
+        let text_y = (bounds.y + (PANEL_ROW_HEIGHT - GRAPH_TEXT_SIZE as f32) / 2.0) as i32;
+        ctx.draw_text(
+            text.as_str(),
+            bounds.x as i32 + 5,
+            text_y,
+            GRAPH_TEXT_SIZE,
+            Color::RAYWHITE,
         );
+        for (button, label) in [(minus, "-"), (plus, "+")] {
+            ctx.draw_rectangle_rec(button, PANEL_BUTTON_COLOR);
+            let label_x =
+                button.x + (button.width - measure_text(label, HUD_TEXT_SIZE) as f32) / 2.0;
+            let label_y = button.y + (button.height - HUD_TEXT_SIZE as f32) / 2.0;
+            ctx.draw_text(
+                label,
+                label_x as i32,
+                label_y as i32,
+                HUD_TEXT_SIZE,
+                Color::RAYWHITE,
+            );
+        }
     }
+    let (last_row, _, _) = panel_row_rects(screen_width, PANEL_PARAMS.len() - 1);
+    let bounds = Rectangle::new(
+        last_row.x,
+        last_row.y - (PANEL_PARAMS.len() - 1) as f32 * PANEL_ROW_HEIGHT,
+        PANEL_WIDTH,
+        PANEL_PARAMS.len() as f32 * PANEL_ROW_HEIGHT,
+    );
+    ctx.draw_rectangle_lines_ex(bounds, 1, WALL_COLOR);
+}
 
-    // Draw walls
-    for wall in &world.walls {
-        ctx.draw_rectangle_rec(wall, WALL_COLOR);
+fn render_fitness_graph(ctx: &mut impl RaylibDraw, world: &World) {
+    let bounds = Rectangle::new(
+        world.config.screen_width as f32 - GRAPH_SIZE.x - GRAPH_MARGIN,
+        GRAPH_MARGIN,
+        GRAPH_SIZE.x,
+        GRAPH_SIZE.y,
+    );
+    ctx.draw_rectangle_rec(bounds, GRAPH_BACKGROUND_COLOR);
+    ctx.draw_rectangle_lines_ex(bounds, 1, WALL_COLOR);
+    ctx.draw_text(
+        "Best fitness",
+        bounds.x as i32 + 5,
+        bounds.y as i32 + 5,
+        GRAPH_TEXT_SIZE,
+        Color::RAYWHITE,
+    );
+
+    let history = &world.best_fitness_history;
+    if history.len() < 2 {
+        return;
     }
+    let min = history.iter().copied().fold(f32::MAX, f32::min);
+    let max = history.iter().copied().fold(f32::MIN, f32::max);
+    let range = (max - min).max(f32::EPSILON);
 
-    // Draw target
-    ctx.draw_circle_v(world.target, TARGET_RADIUS, TARGET_OUTER_COLOR);
-    ctx.draw_circle_v(world.target, TARGET_RADIUS / 2.0, TARGET_INNER_COLOR);
-    ctx.draw_rectangle_rec(world.timer_rect, TIMER_RECT_COLOR);
+    // Plot area leaves room for the labels above and below it
+    let plot = Rectangle::new(
+        bounds.x + 5.0,
+        bounds.y + 20.0,
+        bounds.width - 10.0,
+        bounds.height - 35.0,
+    );
+    let last_ind = (history.len() - 1) as f32;
+    let to_point = |ind: usize| {
+        Vector2::new(
+            plot.x + plot.width * ind as f32 / last_ind,
+            plot.y + plot.height * (1.0 - (history[ind] - min) / range),
+        )
+    };
+    for ind in 1..history.len() {
+        ctx.draw_line_ex(to_point(ind - 1), to_point(ind), 2.0, GRAPH_LINE_COLOR);
+    }
 
+    let bottom = (plot.y + plot.height) as i32 + 4;
+    let right = (bounds.x + bounds.width) as i32 - 5;
+    let max_label = format!("{:.4}", max);
+    let generation_label = format!("gen {}", history.len());
     ctx.draw_text(
-        format!("Generation {}", world.generation).as_str(),
-        20,
-        SCREEN_HEIGHT - 40,
-        20,
+        max_label.as_str(),
+        right - measure_text(max_label.as_str(), GRAPH_TEXT_SIZE),
+        bounds.y as i32 + 5,
+        GRAPH_TEXT_SIZE,
+        Color::RAYWHITE,
+    );
+    ctx.draw_text(
+        format!("{:.4}", min).as_str(),
+        plot.x as i32,
+        bottom,
+        GRAPH_TEXT_SIZE,
+        Color::RAYWHITE,
+    );
+    ctx.draw_text(
+        generation_label.as_str(),
+        right - measure_text(generation_label.as_str(), GRAPH_TEXT_SIZE),
+        bottom,
+        GRAPH_TEXT_SIZE,
         Color::RAYWHITE,
     );
 }
 
-fn main() {
+// Every world with all of its rockets mid-flight, written to a temporary file first so a
+// crash in the middle of a save leaves the last one intact
+fn save_worlds(worlds: &[World], path: &str) -> Result<(), String> {
+    let json = serde_json::to_string(worlds).map_err(|err| err.to_string())?;
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, json).map_err(|err| format!("failed to write '{}': {}", tmp_path, err))?;
+    fs::rename(&tmp_path, path).map_err(|err| format!("failed to write '{}': {}", path, err))
+}
+
+fn load_worlds(path: &str) -> Result<Vec<World>, String> {
+    let json =
+        fs::read_to_string(path).map_err(|err| format!("failed to read '{}': {}", path, err))?;
+    let mut worlds: Vec<World> = serde_json::from_str(&json)
+        .map_err(|err| format!("failed to parse '{}': {}", path, err))?;
+    if worlds.is_empty() {
+        return Err(format!("'{}' doesn't hold any worlds", path));
+    }
+    worlds.iter_mut().for_each(World::restore);
+    Ok(worlds)
+}
+
+// Saves every world to `WORLD_PATH` whenever `interval` has passed since the last save
+struct Autosave {
+    interval: Duration,
+    last_save: Instant,
+}
+
+impl Autosave {
+    fn new(secs: f32) -> Self {
+        Self {
+            interval: Duration::from_secs_f32(secs),
+            last_save: Instant::now(),
+        }
+    }
+
+    fn tick(&mut self, worlds: &mut [World]) {
+        if self.last_save.elapsed() < self.interval {
+            return;
+        }
+        self.last_save = Instant::now();
+        if let Err(err) = save_worlds(worlds, WORLD_PATH) {
+            eprintln!("WARNING: failed to autosave: {}", err);
+        }
+        // Stats up to the save shouldn't be lost to a crash either
+        if let Some(writer) = &mut worlds[0].stats_writer {
+            if let Err(err) = writer.flush() {
+                eprintln!("WARNING: failed to flush generation stats: {}", err);
+            }
+        }
+    }
+}
+
+// Vsync is the only thing holding the frame rate back, no target FPS is ever set
+fn set_uncapped(rl: &mut RaylibHandle, uncapped: bool) {
+    let vsync = WindowState::default().set_vsync_hint(true);
+    if uncapped {
+        rl.clear_window_state(vsync);
+        println!("Frame rate uncapped, vsync off");
+    } else {
+        rl.set_window_state(vsync);
+        println!("Frame rate capped, vsync on");
+    }
+}
+
+// Closes the window once `max_generations` generations have finished, if given
+// Every world shares the same map and races in the same window, the first one owns
+// the controls that only make sense for a single population
+// `split` puts every world on its own side of the window instead of racing them in one arena,
+// `uncapped` starts without vsync
+fn run_window(
+    worlds: &mut [World],
+    max_generations: Option<u32>,
+    mut autosave: Option<Autosave>,
+    split: bool,
+    uncapped: bool,
+) {
+    let sides = if split { worlds.len() as i32 } else { 1 };
     let (mut rl, thread) = raylib::init()
-        .size(SCREEN_WIDTH, SCREEN_HEIGHT)
+        .size(
+            worlds[0].config.screen_width * sides,
+            worlds[0].config.screen_height,
+        )
         .title("Smart Rockets")
         .msaa_4x()
         .vsync()
         .build();
 
-    let mut world = World::new();
+    for world in worlds.iter_mut() {
+        world.animate_respawn = true;
+    }
+    let mut view = View::new();
+    view.split_width = split.then_some(worlds[0].config.screen_width as f32);
+    if uncapped {
+        view.uncapped = true;
+        set_uncapped(&mut rl, true);
+    }
     let mut pause = false;
+    // Long exposure canvas, only kept while `view.spray_trails` is on
+    let mut spray: Option<RenderTexture2D> = None;
+    // Frame time not yet simulated
+    let mut accumulator = 0.0;
     while !rl.window_should_close() {
+        let mut screenshot = None;
+        let world = &mut worlds[0];
         // Handle input phase
-        match handle_input(&rl) {
-            Actions::Pause => pause = !pause,
+        match handle_input(&rl, &mut view, &world.walls) {
+            // Cuts a victory pause short instead of pausing
+            Actions::Pause if view.victory.is_some() => view.victory = None,
+            Actions::Pause => {
+                pause = !pause;
+                // Walls can only be edited while paused
+                if !pause {
+                    view.edit_mode = false;
+                    view.drag_start = None;
+                }
+            }
+            Actions::ToggleEditMode => {
+                view.edit_mode = !view.edit_mode;
+                view.drag_start = None;
+                view.grabbed_wall = None;
+                pause |= view.edit_mode;
+            }
+            Actions::AddWall(wall) => worlds.iter_mut().for_each(|el| el.add_wall(wall)),
+            Actions::RemoveWall(pos) => worlds.iter_mut().for_each(|el| el.remove_wall_at(pos)),
+            Actions::MoveWall(ind, corner) => {
+                worlds.iter_mut().for_each(|el| el.move_wall(ind, corner))
+            }
+            Actions::PlaceTarget(pos) if pause => {
+                worlds.iter_mut().for_each(|el| el.place_target(pos))
+            }
+            // Tell the click apart from one that missed a wall it meant to grab
+            Actions::PlaceTarget(_) => println!("Pause with Space to move the target"),
+            Actions::HistoryBack => world.step_history(false),
+            Actions::HistoryForward => world.step_history(true),
+            Actions::SaveBest => match world.save_best(BEST_GENOME_PATH) {
+                Ok(()) => println!("Saved best genome to {}", BEST_GENOME_PATH),
+                Err(err) => eprintln!("ERROR: failed to save best genome: {}", err),
+            },
+            Actions::SaveGeneration => match world.save_generation(GENERATION_PATH) {
+                Ok(()) => println!("Saved generation to {}", GENERATION_PATH),
+                Err(err) => eprintln!("ERROR: failed to save generation: {}", err),
+            },
+            // A step that lands on the generation boundary only runs the restart. The fade
+            // would only sit frozen between steps and hold the step up, so stepping skips it
+            Actions::Step if pause => {
+                for world in worlds.iter_mut() {
+                    world.end_respawn_fade();
+                    update(world);
+                    world.end_respawn_fade();
+                }
+            }
+            // Kept the same on every world, the ticks run them all at the first one's speed
+            Actions::SpeedUp => worlds
+                .iter_mut()
+                .for_each(|el| el.sim_speed = (el.sim_speed * 2).min(MAX_SIM_SPEED)),
+            Actions::SlowDown => worlds
+                .iter_mut()
+                .for_each(|el| el.sim_speed = (el.sim_speed / 2).max(1)),
+            Actions::LoadBest => match world.load_best(BEST_GENOME_PATH) {
+                Ok(()) => println!("Loaded {}, it will fly next generation", BEST_GENOME_PATH),
+                Err(err) => eprintln!("ERROR: failed to load best genome: {}", err),
+            },
+            Actions::ToggleTrails => view.show_trails = !view.show_trails,
+            Actions::ToggleGraph => view.show_graph = !view.show_graph,
+            Actions::ToggleHeatmap => view.show_heatmap = !view.show_heatmap,
+            Actions::ToggleDistances => view.show_distances = !view.show_distances,
+            Actions::ToggleMinimap => view.show_minimap = !view.show_minimap,
+            Actions::TogglePanel => view.show_panel = !view.show_panel,
+            Actions::ToggleLeader => view.show_leader = !view.show_leader,
+            Actions::ToggleLineage => view.show_lineage = !view.show_lineage,
+            Actions::ToggleAgeTint => view.age_tint = !view.age_tint,
+            Actions::ToggleTooltip => view.show_tooltip = !view.show_tooltip,
+            Actions::ToggleLandscape => view.show_landscape = !view.show_landscape,
+            Actions::ToggleTriangles => view.triangle_rockets = !view.triangle_rockets,
+            Actions::ToggleTimerRing => view.timer_ring = !view.timer_ring,
+            // A canvas only covers one arena
+            Actions::ToggleSpray if !split => view.spray_trails = !view.spray_trails,
+            Actions::CycleDeathOverlay => view.death_overlay = view.death_overlay.next(),
+            Actions::ToggleLeaderboard => view.show_leaderboard = !view.show_leaderboard,
+            Actions::ToggleFrozenPaths => view.show_frozen_paths = !view.show_frozen_paths,
+            Actions::ToggleVictoryPause => {
+                view.victory_pause = !view.victory_pause;
+                view.victory = None;
+            }
+            Actions::ToggleUncapped => {
+                view.uncapped = !view.uncapped;
+                set_uncapped(&mut rl, view.uncapped);
+            }
+            Actions::Screenshot => {
+                let best = world.best_fitness_history.last().copied().unwrap_or(0.0);
+                screenshot = Some(format!("gen_{}_best_{:.4}.png", world.generation, best));
+            }
+            Actions::AdjustParam(param, steps) => {
+                worlds.iter_mut().for_each(|el| param.adjust(el, steps))
+            }
+            Actions::KillAll => worlds.iter_mut().for_each(World::kill_all),
             Actions::Reset => {
-                world.restart();
+                for world in worlds.iter_mut() {
+                    world.restart();
+                    world.ghost_path.clear();
+                    world.death_history.clear();
+                    world.frozen_paths.clear();
+                }
                 println!("Restarted")
             }
             _ => {}
         };
 
         // Update phase
-        if !pause {
-            update(&mut world);
+        let frame_time = rl.get_frame_time();
+        if let Some((_, _, time_left)) = &mut view.victory {
+            *time_left -= frame_time;
+            if *time_left <= 0.0 {
+                view.victory = None;
+            }
+        }
+        let running = !pause && view.victory.is_none();
+        if running {
+            worlds.iter_mut().for_each(|el| el.run_time += frame_time);
+        }
+        // The first world's generation counts, the others may be ahead or behind it
+        let reached_max =
+            |world: &World| max_generations.is_some_and(|max| world.generation >= max);
+        if running {
+            accumulator += frame_time;
+            // Uncapped frames can be far shorter than a tick, each one still gets a tick so
+            // training runs as fast as frames can be drawn. A tick is always the same step
+            if view.uncapped {
+                accumulator = accumulator.max(SIM_TICK);
+            }
+            let mut ticks = 0;
+            'ticks: while accumulator >= SIM_TICK && ticks < MAX_TICKS_PER_FRAME {
+                accumulator -= SIM_TICK;
+                ticks += 1;
+                for _ in 0..worlds[0].sim_speed {
+                    for (world_ind, world) in worlds.iter_mut().enumerate() {
+                        let had_success = world.first_success.is_some();
+                        update(world);
+                        if let (false, true, Some(rocket_ind)) =
+                            (had_success, view.victory_pause, world.first_success)
+                        {
+                            view.victory = Some((world_ind, rocket_ind, VICTORY_PAUSE_SECS));
+                        }
+                    }
+                    if reached_max(&worlds[0]) || view.victory.is_some() {
+                        break 'ticks;
+                    }
+                }
+            }
+            if ticks == MAX_TICKS_PER_FRAME {
+                accumulator = 0.0;
+            }
+        } else {
+            accumulator = 0.0;
+        }
+        if reached_max(&worlds[0]) {
+            break;
+        }
+        if let Some(autosave) = &mut autosave {
+            autosave.tick(worlds);
+        }
+
+        // The canvas covers the arena, which pans and zooms inside the window, so it never
+        // needs a new size and is only made when the trails are turned on
+        if !view.spray_trails {
+            spray = None;
+        }
+        if view.spray_trails && spray.is_none() {
+            let (width, height) = (
+                worlds[0].config.screen_width,
+                worlds[0].config.screen_height,
+            );
+            match rl.load_render_texture(&thread, width as u32, height as u32) {
+                Ok(mut canvas) => {
+                    (&mut rl)
+                        .begin_texture_mode(&thread, &mut canvas)
+                        .clear_background(BACKGROUND_COLOR);
+                    spray = Some(canvas);
+                }
+                Err(err) => {
+                    eprintln!("ERROR: failed to create the trail canvas: {}", err);
+                    view.spray_trails = false;
+                }
+            }
         }
 
         // Render phase
-        let ctx = rl.begin_drawing(&thread);
-        render(ctx, &world);
+        let mut ctx = rl.begin_drawing(&thread);
+        if let (true, Some(canvas)) = (running, &mut spray) {
+            render_spray(&mut ctx.begin_texture_mode(&thread, canvas), worlds, &view);
+        }
+        render(ctx, worlds, &view, screenshot.as_deref(), spray.as_ref());
+    }
+}
+
+fn run_headless(worlds: &mut [World], generations: u32, mut autosave: Option<Autosave>) {
+    for ind in 0..worlds.len() {
+        while worlds[ind].generation < generations {
+            update(&mut worlds[ind]);
+            if let Some(autosave) = &mut autosave {
+                autosave.tick(worlds);
+            }
+        }
+    }
+}
+
+// Runs every combination of `SWEEP_MUTATION_RATES` and `SWEEP_ROCKET_COUNTS` from the same
+// seed and prints one CSV row per combination
+fn run_sweep(config: &SimConfig, seed: u64, generations: u32) {
+    println!("mutation_rate,rocket_count,first_solved_generation,best_fitness");
+    for &mutation_rate in &SWEEP_MUTATION_RATES {
+        for &rocket_count in &SWEEP_ROCKET_COUNTS {
+            let sweep_config = SimConfig {
+                mutation_rate,
+                rocket_count,
+                // The sweep is over fixed population sizes
+                population_schedule: PopulationSchedule::Constant,
+                ..config.clone()
+            };
+            let mut world = World::new(sweep_config, StdRng::seed_from_u64(seed));
+            run_headless(std::slice::from_mut(&mut world), generations, None);
+            let best_fitness = world
+                .best_fitness_history
+                .iter()
+                .copied()
+                .fold(0.0, f32::max);
+            let first_solved = world
+                .first_solved_generation
+                .map_or(String::new(), |generation| generation.to_string());
+            println!(
+                "{},{},{},{}",
+                mutation_rate, rocket_count, first_solved, best_fitness
+            );
+        }
+    }
+}
+
+// Runs the config alone and from a fixed seed so runs before and after a change compare fairly
+fn run_benchmark(config: &SimConfig, seed: u64, generations: u32) {
+    let mut world = World::new(config.clone(), StdRng::seed_from_u64(seed));
+    world.phase_times = Some(PhaseTimes::default());
+    let start = Instant::now();
+    while world.generation < generations {
+        update(&mut world);
+    }
+    let total = start.elapsed();
+    if let Some(times) = &world.phase_times {
+        times.report(total, world.generation, world.sim_frames);
+    }
+}
+
+fn print_summary(worlds: &[World]) {
+    if let [world] = worlds {
+        print_world_summary(world);
+        return;
+    }
+    for (ind, world) in worlds.iter().enumerate() {
+        println!("Species {} ({:?}):", ind + 1, world.selection_strategy);
+        print_world_summary(world);
+    }
+    // Ties go to the species listed first
+    let winner = worlds
+        .iter()
+        .enumerate()
+        .filter_map(|(ind, world)| world.first_solved_generation.map(|gen| (gen, ind)))
+        .min();
+    match winner {
+        Some((generation, ind)) => println!(
+            "First to solve: species {} in generation {}",
+            ind + 1,
+            generation
+        ),
+        None => println!("First to solve: none"),
+    }
+}
+
+fn print_world_summary(world: &World) {
+    let best_fitness = world
+        .best_fitness_history
+        .iter()
+        .copied()
+        .fold(0.0, f32::max);
+    println!("Generations: {}", world.generation);
+    println!("Best fitness: {}", best_fitness);
+    match world.fastest_success {
+        Some(frames) => println!("Fastest success: {} frames", frames),
+        None => println!("Fastest success: target never reached"),
+    }
+    match world.first_solved_generation {
+        Some(generation) => println!("First solved: generation {}", generation),
+        None => println!("First solved: never"),
+    }
+    let solved_label = format!(
+        "Solved ({:.0}% success)",
+        world.config.solved_success_rate * 100.0
+    );
+    match world.solved_generation {
+        Some(generation) => println!("{}: generation {}", solved_label, generation),
+        None => println!("{}: never", solved_label),
+    }
+    if world.config.difficulty_ramp.is_some() {
+        println!("Difficulty reached: level {}", world.ramp_solves.len());
+    }
+    println!("Total successful rockets: {}", world.total_successes);
+}
+
+fn main() {
+    let args = match Args::parse() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let load_config = |path: &str| match SimConfig::load(path) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Some(generations) = args.benchmark_generations {
+        run_benchmark(
+            &load_config(&args.config_path),
+            args.seed.unwrap_or(SWEEP_DEFAULT_SEED),
+            generations,
+        );
+        return;
+    }
+    if let Some(generations) = args.sweep_generations {
+        run_sweep(
+            &load_config(&args.config_path),
+            args.seed.unwrap_or(SWEEP_DEFAULT_SEED),
+            generations,
+        );
+        return;
+    }
+
+    // A resumed run carries its own config, seed and rival
+    let mut worlds = match &args.resume_path {
+        Some(path) => match load_worlds(path) {
+            Ok(worlds) => worlds,
+            Err(err) => {
+                eprintln!("ERROR: failed to resume: {}", err);
+                std::process::exit(1);
+            }
+        },
+        None => {
+            let config = load_config(&args.config_path);
+            let rng = match args.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+
+            // A replay flies the saved generation alone, there is nothing for a rival to race,
+            // and a split screen already has its own world to compare against
+            let rival_selection = config
+                .rival_selection
+                .filter(|_| args.replay_path.is_none() && !args.compare());
+            // Seeded from a clone so the first population flies the same with or without a rival
+            let rival = rival_selection.map(|strategy| {
+                let rival_config = SimConfig {
+                    selection: strategy,
+                    ..config.clone()
+                };
+                let mut rival = World::new(rival_config, StdRng::seed_from_u64(rng.clone().gen()));
+                rival.species_color = RIVAL_ROCKET_COLOR;
+                rival
+            });
+            // Without a seed of its own it starts from the same one, so only the
+            // configs differ
+            let compare = args.compare().then(|| {
+                let compare_config = match &args.compare_config_path {
+                    Some(path) => load_config(path),
+                    None => config.clone(),
+                };
+                // Both sides fly the same map
+                let compare_config = SimConfig {
+                    screen_width: config.screen_width,
+                    screen_height: config.screen_height,
+                    targets: config.targets.clone(),
+                    target_radius: config.target_radius,
+                    target_motion: config.target_motion,
+                    spawn: config.spawn,
+                    spawn_angle: config.spawn_angle,
+                    boundary_mode: config.boundary_mode,
+                    walls: config.walls.clone(),
+                    polygon_walls: config.polygon_walls.clone(),
+                    circle_obstacles: config.circle_obstacles.clone(),
+                    gates: config.gates.clone(),
+                    ..compare_config
+                };
+                let compare_rng = match args.compare_seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => rng.clone(),
+                };
+                World::new(compare_config, compare_rng)
+            });
+            let mut world = World::new(config, rng);
+            if let Some(dir) = &args.init_from_dir {
+                match world.init_from(dir) {
+                    Ok(count) => println!("Planted {} genomes from {}", count, dir),
+                    Err(err) => {
+                        eprintln!("ERROR: failed to seed the first generation: {}", err);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Some(path) = &args.replay_path {
+                if let Err(err) = world.load_generation(path) {
+                    eprintln!("ERROR: failed to load generation: {}", err);
+                    std::process::exit(1);
+                }
+            }
+            std::iter::once(world).chain(rival).chain(compare).collect()
+        }
+    };
+    if let Some(path) = worlds[0].config.stats_path.clone() {
+        // A resumed run adds on to the stats written before it was saved
+        let writer = if args.resume_path.is_some() {
+            StatsWriter::append(&path)
+        } else {
+            StatsWriter::create(&path)
+        };
+        match writer {
+            Ok(writer) => worlds[0].stats_writer = Some(writer),
+            Err(err) => {
+                eprintln!("ERROR: failed to open stats file '{}': {}", path, err);
+                std::process::exit(1);
+            }
+        }
+    }
+    let autosave = args.autosave_secs.map(Autosave::new);
+
+    match args.headless_generations {
+        Some(generations) => {
+            run_headless(&mut worlds, generations, autosave);
+            print_summary(&worlds);
+        }
+        None => {
+            run_window(
+                &mut worlds,
+                args.max_generations,
+                autosave,
+                args.compare(),
+                args.uncapped,
+            );
+            if args.max_generations.is_some() {
+                print_summary(&worlds);
+            }
+        }
+    }
+
+    if let Some(writer) = &mut worlds[0].stats_writer {
+        if let Err(err) = writer.flush() {
+            eprintln!("WARNING: failed to flush generation stats: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Few short rockets, so tests can fly whole generations quickly
+    fn test_config() -> SimConfig {
+        SimConfig {
+            rocket_count: 20,
+            gene_len: 60,
+            ..SimConfig::default()
+        }
+    }
+
+    fn test_world(config: SimConfig) -> World {
+        World::new(config, StdRng::seed_from_u64(7))
+    }
+
+    #[test]
+    fn fitness_is_finite_with_every_rocket_on_the_target() {
+        for fitness_kind in [
+            FitnessKind::Relative,
+            FitnessKind::Inverse,
+            FitnessKind::Squared,
+        ] {
+            let mut world = test_world(SimConfig {
+                fitness_kind,
+                ..test_config()
+            });
+            let target = world.targets[0];
+            for rocket in &mut world.rockets {
+                rocket.pos = target;
+            }
+            world.calc_fitness();
+            assert!(
+                world.rockets.iter().all(|el| el.dna.fitness.is_finite()),
+                "{:?} gave a fitness that isn't finite",
+                fitness_kind
+            );
+        }
+    }
+
+    #[test]
+    fn breeding_from_all_zero_fitness_does_not_panic() {
+        for selection in [
+            SelectionStrategy::Roulette,
+            SelectionStrategy::Rank,
+            SelectionStrategy::Tournament { k: 3 },
+        ] {
+            // Relative fitness isn't scaled up to the best rocket, so the path length penalty
+            // takes every rocket down to zero
+            let mut world = test_world(SimConfig {
+                selection,
+                fitness_kind: FitnessKind::Relative,
+                path_length_penalty: 1.0,
+                ..test_config()
+            });
+            let far_corner = Vector2::new(
+                world.config.screen_width as f32,
+                world.config.screen_height as f32,
+            );
+            for rocket in &mut world.rockets {
+                rocket.state = RocketState::Dead;
+                rocket.pos = far_corner;
+                rocket.path_length = 1000.0;
+            }
+            world.restart();
+            assert!(world.best_fitness_history[0] < 0.01);
+            if let SelectionStrategy::Roulette = selection {
+                // Every share floors to nothing, so parents have to be picked without it
+                assert!(world.mating_pool.is_empty());
+            }
+            assert_eq!(world.rockets.len(), world.config.rocket_count);
+            assert!(world
+                .rockets
+                .iter()
+                .all(|el| el.dna.genes.len() == world.config.gene_len));
+        }
+    }
+
+    #[test]
+    fn generation_ends_early_once_every_rocket_crashed() {
+        // A wall over the spawn crashes every rocket on its first frame
+        let mut world = test_world(SimConfig {
+            walls: vec![Rectangle::new(400.0, 500.0, 200.0, 150.0)],
+            ..test_config()
+        });
+        let flight_len = world.flight_len();
+        let mut frames = 0;
+        while world.generation == 0 {
+            update(&mut world);
+            frames += 1;
+            assert!(frames < flight_len, "flew every frame of the generation");
+        }
+        assert_eq!(frames, 2);
+    }
+
+    #[test]
+    fn reaching_the_target_takes_one_off_alive_count() {
+        let mut world = test_world(test_config());
+        let alive_before = world.alive_count;
+        world.rockets[0].pos = world.targets[0];
+        update(&mut world);
+        assert_eq!(world.rockets[0].state, RocketState::Successful);
+        assert_eq!(world.alive_count, alive_before - 1);
+    }
+
+    #[test]
+    fn rank_selection_weights_by_rank_not_fitness() {
+        let mut world = test_world(SimConfig {
+            rocket_count: 4,
+            selection: SelectionStrategy::Rank,
+            ..test_config()
+        });
+        // One outlier that would take nearly the whole roulette pool
+        for (rocket, fitness) in world.rockets.iter_mut().zip([0.001, 1000.0, 0.5, 0.2]) {
+            rocket.dna.fitness = fitness;
+        }
+        world.gen_rank_mating_pool();
+        let picks = 20_000;
+        let mut counts = [0; 4];
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..picks {
+            counts[world.pick_parent(&mut rng)] += 1;
+        }
+        // Fittest to least fit get 4, 3, 2 and 1 shares out of 10
+        for (ind, expected) in [(1, 0.4), (2, 0.3), (3, 0.2), (0, 0.1)] {
+            let share = counts[ind] as f32 / picks as f32;
+            assert!(
+                (share - expected).abs() < 0.02,
+                "rocket {} was picked {} of the time instead of {}",
+                ind,
+                share,
+                expected
+            );
+        }
+    }
+
+    // Every genome after a few generations bred on a pool of `threads` threads
+    fn genomes_after(threads: usize) -> Vec<Genes> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            let mut world = test_world(test_config());
+            run_headless(std::slice::from_mut(&mut world), 3, None);
+            world
+                .rockets
+                .iter()
+                .map(|el| el.dna.genes.clone())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn thread_count_does_not_change_the_run() {
+        assert_eq!(genomes_after(1), genomes_after(8));
+    }
+
+    #[test]
+    fn longer_path_scores_lower_at_the_same_distance() {
+        for fitness_kind in [FitnessKind::Relative, FitnessKind::Inverse] {
+            let mut world = test_world(SimConfig {
+                fitness_kind,
+                path_length_penalty: 0.00001,
+                ..test_config()
+            });
+            let pos = world.targets[0] + Vector2::new(100.0, 0.0);
+            for rocket in &mut world.rockets {
+                rocket.pos = pos;
+                rocket.path_length = 300.0;
+            }
+            world.rockets[1].path_length = 600.0;
+            world.calc_fitness();
+            assert!(
+                world.rockets[1].dna.fitness < world.rockets[0].dna.fitness,
+                "{:?} didn't penalize the longer path",
+                fitness_kind
+            );
+        }
+    }
+
+    fn angle_dna(genes: Vec<f32>) -> DNA {
+        DNA {
+            genes: Genes::Angle(genes),
+            curr_gene: 0,
+            fitness: 0.0,
+            lineage_hue: 0.0,
+        }
+    }
+
+    #[test]
+    fn crossover_at_the_ends_copies_one_parent() {
+        let parent_a = angle_dna(vec![1.0, 2.0, 3.0, 4.0]);
+        let parent_b = angle_dna(vec![-1.0, -2.0, -3.0, -4.0]);
+        assert_eq!(
+            DNA::crossover_at(&parent_a, &parent_b, 0).genes,
+            parent_b.genes
+        );
+        assert_eq!(
+            DNA::crossover_at(&parent_a, &parent_b, 4).genes,
+            parent_a.genes
+        );
+        assert_eq!(
+            DNA::crossover_at(&parent_a, &parent_b, 3).genes,
+            Genes::Angle(vec![1.0, 2.0, 3.0, -4.0])
+        );
+    }
+
+    #[test]
+    fn crossover_of_unequal_lengths_takes_the_tail_from_parent_b() {
+        let short = angle_dna(vec![1.0, 2.0, 3.0]);
+        let long = angle_dna(vec![-1.0, -2.0, -3.0, -4.0, -5.0]);
+        assert_eq!(
+            DNA::crossover_at(&short, &long, 2).genes,
+            Genes::Angle(vec![1.0, 2.0, -3.0, -4.0, -5.0])
+        );
+        assert_eq!(
+            DNA::crossover_at(&long, &short, 3).genes,
+            Genes::Angle(vec![-1.0, -2.0, -3.0])
+        );
+    }
+
+    #[test]
+    fn gaussian_mutation_keeps_genes_within_degree_change() {
+        let degree_change = 10.0;
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut dna = angle_dna(vec![9.5; 200]);
+        for _ in 0..20 {
+            DNA::mutate(
+                &mut dna,
+                MutationKind::Gaussian { std: 50.0 },
+                1.0,
+                |_| degree_change,
+                1.0,
+                &mut rng,
+            );
+            let Genes::Angle(genes) = &dna.genes else {
+                unreachable!()
+            };
+            assert!(genes
+                .iter()
+                .all(|el| (-degree_change..=degree_change).contains(el)));
+        }
+    }
+
+    #[test]
+    fn stuck_population_gets_randomized_past_the_elites() {
+        let elite_count = 2;
+        let mut world = test_world(SimConfig {
+            mutation_rate: 0.0,
+            elite_count,
+            catastrophe: Some(Catastrophe {
+                generations: 3,
+                fraction: 0.5,
+            }),
+            ..test_config()
+        });
+        let blank = Genes::Angle(vec![0.0; world.config.gene_len]);
+        for rocket in &mut world.rockets {
+            rocket.dna.genes = blank.clone();
+        }
+        // Nothing can beat this, so every generation counts as stuck
+        world.best_fitness_ever = f32::MAX;
+        let is_blank = |rocket: &Rocket| rocket.dna.genes == blank;
+        for _ in 0..2 {
+            world.restart();
+            assert!(world.rockets.iter().all(is_blank));
+        }
+        // The lower the index the further from the target, so the worst rockets sit right
+        // after the elites' slots, away from the end where the seed goes
+        let target = world.targets[0];
+        let count = world.rockets.len();
+        for (ind, rocket) in world.rockets.iter_mut().enumerate() {
+            rocket.pos = target + Vector2::new(0.0, 10.0 * (count - ind) as f32);
+        }
+        let seed = Genes::Angle(vec![1.0; world.config.gene_len]);
+        world.seed_dna = Some(DNA {
+            genes: seed.clone(),
+            ..angle_dna(vec![])
+        });
+        world.restart();
+        // 9 of the 18 bred children are the worst ones, and they start over
+        let worst = elite_count..elite_count + 9;
+        for (ind, rocket) in world.rockets.iter().enumerate() {
+            if worst.contains(&ind) {
+                assert!(!is_blank(rocket) && rocket.dna.genes != seed, "{}", ind);
+            } else if ind == count - 1 {
+                assert_eq!(rocket.dna.genes, seed);
+            } else {
+                assert!(is_blank(rocket), "{}", ind);
+            }
+        }
+    }
+
+    #[test]
+    fn resuming_a_save_flies_on_like_the_original() {
+        // Enough rockets that diversity is sampled with the world's rng, and path distance so
+        // the distance fields left out of the save have to be rebuilt
+        let config = SimConfig {
+            rocket_count: 30,
+            path_distance: true,
+            ..test_config()
+        };
+        let mut original = test_world(config);
+        original.animate_respawn = true;
+        run_headless(std::slice::from_mut(&mut original), 1, None);
+        for _ in 0..20 {
+            update(&mut original);
+        }
+        let path = std::env::temp_dir().join("smart_rockets_resume_test.json");
+        let path = path.to_str().unwrap();
+        save_worlds(std::slice::from_ref(&original), path).unwrap();
+        let mut resumed = load_worlds(path).unwrap().remove(0);
+        fs::remove_file(path).unwrap();
+        run_headless(std::slice::from_mut(&mut original), 4, None);
+        run_headless(std::slice::from_mut(&mut resumed), 4, None);
+        let genomes = |world: &World| -> Vec<Genes> {
+            world
+                .rockets
+                .iter()
+                .map(|el| el.dna.genes.clone())
+                .collect()
+        };
+        assert_eq!(genomes(&original), genomes(&resumed));
+        assert_eq!(original.best_fitness_history, resumed.best_fitness_history);
+        assert_eq!(original.diversity, resumed.diversity);
+    }
+
+    #[test]
+    fn shorter_saved_genome_keeps_its_genes_when_topped_up() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let saved: Vec<f32> = (0..300).map(|ind| ind as f32 / 100.0).collect();
+        let mut dna = angle_dna(saved.clone());
+        let degree_change = 10.0;
+        dna.fit_to_len(
+            400,
+            InitDistribution::Uniform,
+            |_| degree_change,
+            1.0,
+            &mut rng,
+        );
+        let Genes::Angle(genes) = &dna.genes else {
+            unreachable!()
+        };
+        assert_eq!(genes.len(), 400);
+        assert_eq!(genes[..300], saved[..]);
+        assert!(genes[300..]
+            .iter()
+            .all(|el| (-degree_change..=degree_change).contains(el)));
+    }
+
+    #[test]
+    fn child_without_crossover_or_mutation_is_a_clone_of_parent_a() {
+        let mut world = test_world(SimConfig {
+            crossover_rate: 0.0,
+            selection: SelectionStrategy::Tournament { k: 2 },
+            ..test_config()
+        });
+        for (ind, rocket) in world.rockets.iter_mut().enumerate() {
+            rocket.dna.genes = Genes::Angle(vec![ind as f32; 60]);
+        }
+        // Every rocket's genome differs, so only parent_a's can match
+        for ind in 0..world.rockets.len() {
+            // The same draws `breed_child` starts with
+            let rng = &mut world.child_rng(world.generation + 1, ind);
+            let parent_a = world.pick_parent(rng);
+            let child = world.breed_child(ind, 0.0);
+            assert_eq!(child.dna.genes, world.rockets[parent_a].dna.genes);
+        }
+    }
+
+    #[test]
+    fn length_mutation_keeps_genomes_within_bounds() {
+        let length = LengthMutation {
+            rate: 0.5,
+            min_len: 5,
+            max_len: 10,
+        };
+        let mut rng = StdRng::seed_from_u64(13);
+        let (mut shortest, mut longest) = (usize::MAX, 0);
+        for start_len in [5, 7, 10] {
+            let mut dna = angle_dna(vec![0.0; start_len]);
+            for _ in 0..500 {
+                DNA::mutate_length(&mut dna, length, |_| 10.0, 1.0, &mut rng);
+                let len = dna.genes.len();
+                assert!((length.min_len..=length.max_len).contains(&len));
+                shortest = shortest.min(len);
+                longest = longest.max(len);
+            }
+        }
+        // Genomes did grow and shrink all the way to both bounds
+        assert_eq!((shortest, longest), (length.min_len, length.max_len));
+    }
+
+    #[test]
+    fn loaded_genome_never_replaces_an_elite() {
+        let config = test_config();
+        let mut world = test_world(SimConfig {
+            elite_count: config.rocket_count,
+            ..config
+        });
+        for (ind, rocket) in world.rockets.iter_mut().enumerate() {
+            rocket.dna.genes = Genes::Angle(vec![ind as f32; 60]);
+        }
+        let parents: Vec<Genes> = world
+            .rockets
+            .iter()
+            .map(|el| el.dna.genes.clone())
+            .collect();
+        world.seed_dna = Some(angle_dna(vec![-1.0; 60]));
+        world.restart();
+        assert!(world.seed_dna.is_none());
+        assert!(world
+            .rockets
+            .iter()
+            .all(|el| parents.contains(&el.dna.genes)));
+    }
+
+    #[test]
+    fn gene_len_panel_stays_within_the_length_mutation_range() {
+        let mut world = test_world(SimConfig {
+            length_mutation: Some(LengthMutation {
+                rate: 0.1,
+                min_len: 55,
+                max_len: 100,
+            }),
+            ..test_config()
+        });
+        for _ in 0..3 {
+            Param::GeneLen.adjust(&mut world, 1);
+        }
+        assert_eq!(world.pending_gene_len, Some(100));
+        for _ in 0..3 {
+            Param::GeneLen.adjust(&mut world, -1);
+        }
+        assert_eq!(world.pending_gene_len, Some(55));
+    }
+
+    #[test]
+    fn new_generation_waits_at_the_spawn_during_the_fade() {
+        let mut world = test_world(test_config());
+        world.animate_respawn = true;
+        world.restart();
+        let spawn: Vec<Vector2> = world.rockets.iter().map(|el| el.pos).collect();
+        for _ in 0..RESPAWN_FRAMES {
+            update(&mut world);
+            assert_eq!(world.frame_counter, 0);
+        }
+        assert!(world.fading_rockets.is_empty());
+        update(&mut world);
+        assert_eq!(world.frame_counter, 1);
+        assert!(world
+            .rockets
+            .iter()
+            .zip(&spawn)
+            .any(|(el, &pos)| el.pos != pos));
+    }
+
+    #[test]
+    fn population_schedule_grows_and_shrinks_the_generation() {
+        for (start, end) in [(30, 10), (10, 30)] {
+            let mut world = test_world(SimConfig {
+                population_schedule: PopulationSchedule::Linear {
+                    start,
+                    end,
+                    generations: 4,
+                },
+                rocket_count: start,
+                ..test_config()
+            });
+            assert_eq!(world.rockets.len(), start);
+            for _ in 0..5 {
+                let old_len = world.rockets.len();
+                world.restart();
+                let expected = world.config.population_size(world.generation);
+                assert_eq!(world.rockets.len(), expected);
+                assert_eq!(world.alive_count, expected as i32);
+                // Built from the old generation, so it can only point into that one
+                assert!(!world.mating_pool.is_empty());
+                assert!(world.mating_pool.iter().all(|&ind| ind < old_len));
+            }
+            assert_eq!(world.rockets.len(), end);
+        }
     }
 }
@@ -0,0 +1,88 @@
+use std::fs;
+
+use raylib::prelude::*;
+
+// Map drawn as a grid of characters, one row per line:
+//   `#` wall, `T` target, `S` rocket spawn, `.` or a space for empty
+// The grid is stretched over the whole screen, so cells don't have to be square
+pub struct MapLayout {
+    pub walls: Vec<Rectangle>,
+    pub target: Vector2,
+    pub spawn: Vector2,
+}
+
+impl MapLayout {
+    pub fn load(path: &str, screen_width: f32, screen_height: f32) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read map '{}': {}", path, err))?;
+        Self::parse(&contents, screen_width, screen_height)
+            .map_err(|err| format!("invalid map '{}': {}", path, err))
+    }
+
+    fn parse(contents: &str, screen_width: f32, screen_height: f32) -> Result<Self, String> {
+        let rows: Vec<&str> = contents.trim_end().lines().map(str::trim_end).collect();
+        let cols = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        if cols == 0 {
+            return Err("the map is empty".to_string());
+        }
+        let cell = Vector2::new(
+            screen_width / cols as f32,
+            screen_height / rows.len() as f32,
+        );
+        let cell_center = |row: usize, col: usize| {
+            Vector2::new((col as f32 + 0.5) * cell.x, (row as f32 + 0.5) * cell.y)
+        };
+
+        let mut walls = vec![];
+        let mut targets = vec![];
+        let mut spawns = vec![];
+        for (row, line) in rows.iter().enumerate() {
+            // Neighbouring wall cells in a row become a single wall
+            let mut wall_start = None;
+            for (col, ch) in line.chars().chain(std::iter::once('.')).enumerate() {
+                match ch {
+                    '#' => {
+                        wall_start.get_or_insert(col);
+                        continue;
+                    }
+                    'T' => targets.push(cell_center(row, col)),
+                    'S' => spawns.push(cell_center(row, col)),
+                    '.' | ' ' => {}
+                    _ => {
+                        return Err(format!(
+                            "unknown character '{}' at line {}, column {}",
+                            ch,
+                            row + 1,
+                            col + 1
+                        ))
+                    }
+                }
+                if let Some(start) = wall_start.take() {
+                    walls.push(Rectangle::new(
+                        start as f32 * cell.x,
+                        row as f32 * cell.y,
+                        (col - start) as f32 * cell.x,
+                        cell.y,
+                    ));
+                }
+            }
+        }
+
+        match (targets.as_slice(), spawns.as_slice()) {
+            (&[target], &[spawn]) => Ok(Self {
+                walls,
+                target,
+                spawn,
+            }),
+            _ => Err(format!(
+                "expected exactly one 'T' and one 'S', found {} and {}",
+                targets.len(),
+                spawns.len()
+            )),
+        }
+    }
+}
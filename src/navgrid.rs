@@ -0,0 +1,190 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use raylib::prelude::*;
+
+use crate::collision;
+
+// Step costs between neighbouring cells, diagonals are close enough to sqrt(2) times longer
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+// Rings of cells searched around a blocked cell for an open one to measure from
+const OPEN_CELL_RINGS: i32 = 2;
+
+// Distance field over a coarse grid: how far every open cell is from the goal
+// when going around the obstacles instead of straight through them
+pub struct NavGrid {
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    pub goal: Vector2,
+    // In `STRAIGHT_COST`s per cell, none for cells that are blocked or can't reach the goal
+    costs: Vec<Option<u32>>,
+    max_distance: f32,
+}
+
+impl NavGrid {
+    pub fn new(
+        walls: &[Rectangle],
+        polygons: &[Vec<Vector2>],
+        circles: &[(Vector2, f32)],
+        width: f32,
+        height: f32,
+        cell_size: f32,
+        goal: Vector2,
+    ) -> Self {
+        let cols = (width / cell_size).ceil().max(1.0) as usize;
+        let rows = (height / cell_size).ceil().max(1.0) as usize;
+        let blocked: Vec<bool> = (0..cols * rows)
+            .map(|ind| {
+                let cell = Rectangle::new(
+                    (ind % cols) as f32 * cell_size,
+                    (ind / cols) as f32 * cell_size,
+                    cell_size,
+                    cell_size,
+                );
+                let corners = collision::rect_corners(&cell);
+                walls.iter().any(|wall| wall.check_collision_recs(&cell))
+                    || polygons
+                        .iter()
+                        .any(|polygon| collision::convex_push_out(&corners, polygon).is_some())
+                    || circles
+                        .iter()
+                        .any(|&(center, radius)| cell.check_collision_circle_rec(center, radius))
+            })
+            .collect();
+
+        let mut grid = Self {
+            cell_size,
+            cols,
+            rows,
+            goal,
+            costs: vec![None; cols * rows],
+            max_distance: 0.0,
+        };
+        // Dijkstra out from the goal, which counts as open even if a wall touches its cell
+        let start = grid.cell_of(goal);
+        let mut queue = BinaryHeap::new();
+        grid.costs[start] = Some(0);
+        queue.push(Reverse((0, start)));
+        while let Some(Reverse((cost, ind))) = queue.pop() {
+            if grid.costs[ind].is_some_and(|best| best < cost) {
+                continue;
+            }
+            let (col, row) = ((ind % cols) as i32, (ind / cols) as i32);
+            for (d_col, d_row) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                let Some(next) = grid.index(col + d_col, row + d_row) else {
+                    continue;
+                };
+                if blocked[next] {
+                    continue;
+                }
+                let diagonal = d_col != 0 && d_row != 0;
+                // Diagonals can't squeeze between two blocked corners
+                if diagonal
+                    && (grid.index(col + d_col, row).is_none_or(|el| blocked[el])
+                        || grid.index(col, row + d_row).is_none_or(|el| blocked[el]))
+                {
+                    continue;
+                }
+                let next_cost = cost
+                    + if diagonal {
+                        DIAGONAL_COST
+                    } else {
+                        STRAIGHT_COST
+                    };
+                if grid.costs[next].is_none_or(|best| next_cost < best) {
+                    grid.costs[next] = Some(next_cost);
+                    queue.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+        let max_cost = grid.costs.iter().flatten().copied().max().unwrap_or(0);
+        grid.max_distance = grid.to_pixels(max_cost) + cell_size;
+        grid
+    }
+
+    fn index(&self, col: i32, row: i32) -> Option<usize> {
+        if col < 0 || row < 0 || col as usize >= self.cols || row as usize >= self.rows {
+            return None;
+        }
+        Some(row as usize * self.cols + col as usize)
+    }
+
+    // Positions off the grid are clamped into the border cells
+    fn cell_of(&self, pos: Vector2) -> usize {
+        let col = ((pos.x / self.cell_size).floor().max(0.0) as usize).min(self.cols - 1);
+        let row = ((pos.y / self.cell_size).floor().max(0.0) as usize).min(self.rows - 1);
+        row * self.cols + col
+    }
+
+    fn to_pixels(&self, cost: u32) -> f32 {
+        cost as f32 / STRAIGHT_COST as f32 * self.cell_size
+    }
+
+    // Path distance from `pos` to the goal, anything that can't reach it is treated as
+    // further away than every cell that can
+    pub fn distance(&self, pos: Vector2) -> f32 {
+        let ind = self.cell_of(pos);
+        if let Some(cost) = self.costs[ind] {
+            return self.distance_through(ind, cost, pos);
+        }
+        // A rocket that crashed into a wall usually ends up in a cell the wall touches, so
+        // it's measured through the closest open cells around it instead
+        let (col, row) = ((ind % self.cols) as i32, (ind / self.cols) as i32);
+        for radius in 1..=OPEN_CELL_RINGS {
+            let closest = (-radius..=radius)
+                .flat_map(|d_col| (-radius..=radius).map(move |d_row| (d_col, d_row)))
+                .filter(|&(d_col, d_row)| d_col.abs() == radius || d_row.abs() == radius)
+                .filter_map(|(d_col, d_row)| self.index(col + d_col, row + d_row))
+                .filter_map(|next| {
+                    self.costs[next].map(|cost| self.distance_through(next, cost, pos))
+                })
+                .min_by(f32::total_cmp);
+            if let Some(distance) = closest {
+                return distance;
+            }
+        }
+        self.max_distance
+    }
+
+    // Straight from `pos` to the center of cell `ind`, then the path on from there
+    fn distance_through(&self, ind: usize, cost: u32, pos: Vector2) -> f32 {
+        // The goal's own cell measures straight to it
+        if cost == 0 {
+            return (self.goal - pos).length();
+        }
+        let center = Vector2::new(
+            ((ind % self.cols) as f32 + 0.5) * self.cell_size,
+            ((ind / self.cols) as f32 + 0.5) * self.cell_size,
+        );
+        self.to_pixels(cost) + (center - pos).length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rocket_against_a_wall_measures_from_the_open_side() {
+        // Wall from x 95 to 205, so the cells from x 80 to 220 are all blocked
+        let wall = Rectangle::new(95.0, 0.0, 110.0, 300.0);
+        let goal = Vector2::new(50.0, 150.0);
+        let grid = NavGrid::new(&[wall], &[], &[], 400.0, 400.0, 20.0, goal);
+        // Crashed sideways into the wall from the goal's side, and from the far side
+        let near = grid.distance(Vector2::new(88.0, 150.0));
+        let far = grid.distance(Vector2::new(212.0, 150.0));
+        assert!((near - 38.0).abs() < 20.0, "{}", near);
+        assert!(far > near && far < grid.max_distance, "{}", far);
+    }
+}
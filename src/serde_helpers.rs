@@ -0,0 +1,137 @@
+// raylib's math types don't implement serde traits, so these mirror them for (de)serialization
+use raylib::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Vector2")]
+pub struct Vector2Def {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Rectangle")]
+pub struct RectangleDef {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Color")]
+pub struct ColorDef {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Vector2Wrapper(#[serde(with = "Vector2Def")] Vector2);
+
+#[derive(Serialize, Deserialize)]
+struct RectangleWrapper(#[serde(with = "RectangleDef")] Rectangle);
+
+#[derive(Serialize, Deserialize)]
+struct PolygonWrapper(#[serde(with = "vector2_vec")] Vec<Vector2>);
+
+#[derive(Serialize, Deserialize)]
+struct CircleDef {
+    #[serde(with = "Vector2Def")]
+    center: Vector2,
+    radius: f32,
+}
+
+// Circles are written as `{ center = { x, y }, radius }` tables
+pub mod circle_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        circles: &[(Vector2, f32)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            circles
+                .iter()
+                .map(|&(center, radius)| CircleDef { center, radius }),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(Vector2, f32)>, D::Error> {
+        let circles = Vec::<CircleDef>::deserialize(deserializer)?;
+        Ok(circles
+            .into_iter()
+            .map(|el| (el.center, el.radius))
+            .collect())
+    }
+}
+
+pub mod vector2_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(vectors: &[Vector2], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(vectors.iter().map(|&el| Vector2Wrapper(el)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vector2>, D::Error> {
+        let wrapped = Vec::<Vector2Wrapper>::deserialize(deserializer)?;
+        Ok(wrapped.into_iter().map(|el| el.0).collect())
+    }
+}
+
+pub mod vector2_opt {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        vector: &Option<Vector2>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        vector.map(Vector2Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vector2>, D::Error> {
+        let wrapped = Option::<Vector2Wrapper>::deserialize(deserializer)?;
+        Ok(wrapped.map(|el| el.0))
+    }
+}
+
+// Polygons are written as lists of `{ x, y }` vertices
+pub mod polygon_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        polygons: &[Vec<Vector2>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(polygons.iter().map(|el| PolygonWrapper(el.clone())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<Vector2>>, D::Error> {
+        let wrapped = Vec::<PolygonWrapper>::deserialize(deserializer)?;
+        Ok(wrapped.into_iter().map(|el| el.0).collect())
+    }
+}
+
+pub mod rectangle_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(rects: &[Rectangle], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(rects.iter().map(|&el| RectangleWrapper(el)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Rectangle>, D::Error> {
+        let wrapped = Vec::<RectangleWrapper>::deserialize(deserializer)?;
+        Ok(wrapped.into_iter().map(|el| el.0).collect())
+    }
+}
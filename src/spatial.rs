@@ -0,0 +1,121 @@
+use raylib::prelude::*;
+
+// Uniform grid over the screen where every cell lists the rectangles overlapping it,
+// so a collision check only has to look at the rectangles near the thing being tested
+#[derive(Default)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(rects: &[Rectangle], width: f32, height: f32, cell_size: f32) -> Self {
+        let cols = (width / cell_size).ceil().max(1.0) as usize;
+        let rows = (height / cell_size).ceil().max(1.0) as usize;
+        let mut grid = Self {
+            cell_size,
+            cols,
+            rows,
+            cells: vec![vec![]; cols * rows],
+        };
+        for (ind, rect) in rects.iter().enumerate() {
+            let (col_range, row_range) = grid.cell_range(rect);
+            for row in row_range {
+                for col in col_range.clone() {
+                    grid.cells[row * cols + col].push(ind);
+                }
+            }
+        }
+        grid
+    }
+
+    // Anything past the edges is clamped into the border cells, which keeps
+    // overlapping areas in overlapping cells
+    fn cell_range(
+        &self,
+        area: &Rectangle,
+    ) -> (
+        std::ops::RangeInclusive<usize>,
+        std::ops::RangeInclusive<usize>,
+    ) {
+        let to_cell = |value: f32, count: usize| {
+            ((value / self.cell_size).floor().max(0.0) as usize).min(count - 1)
+        };
+        let cols = to_cell(area.x, self.cols)..=to_cell(area.x + area.width, self.cols);
+        let rows = to_cell(area.y, self.rows)..=to_cell(area.y + area.height, self.rows);
+        (cols, rows)
+    }
+
+    // Calls `pred` with the index of every rectangle sharing a cell with `area` until it
+    // returns true. An index can come up more than once when it spans several cells
+    pub fn any_near(&self, area: &Rectangle, mut pred: impl FnMut(usize) -> bool) -> bool {
+        let (col_range, row_range) = self.cell_range(area);
+        for row in row_range {
+            for col in col_range.clone() {
+                if self.cells[row * self.cols + col]
+                    .iter()
+                    .any(|&ind| pred(ind))
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    const WIDTH: f32 = 800.0;
+    const HEIGHT: f32 = 600.0;
+
+    // Anywhere on the screen or hanging a little off its edges
+    fn random_rect(rng: &mut StdRng, max_size: f32) -> Rectangle {
+        Rectangle::new(
+            rng.gen_range(-50.0..WIDTH),
+            rng.gen_range(-50.0..HEIGHT),
+            rng.gen_range(0.0..max_size),
+            rng.gen_range(0.0..max_size),
+        )
+    }
+
+    #[test]
+    fn finds_every_rectangle_a_full_scan_finds() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..50 {
+            let rects: Vec<_> = (0..rng.gen_range(0..40))
+                .map(|_| random_rect(&mut rng, 200.0))
+                .collect();
+            let cell_size = rng.gen_range(10.0..150.0);
+            let grid = SpatialGrid::new(&rects, WIDTH, HEIGHT, cell_size);
+            for _ in 0..100 {
+                let area = random_rect(&mut rng, 60.0);
+                let expected: Vec<_> = (0..rects.len())
+                    .filter(|&ind| rects[ind].check_collision_recs(&area))
+                    .collect();
+                let mut found = vec![];
+                grid.any_near(&area, |ind| {
+                    if rects[ind].check_collision_recs(&area) && !found.contains(&ind) {
+                        found.push(ind);
+                    }
+                    false
+                });
+                found.sort();
+                assert_eq!(
+                    found, expected,
+                    "area {:?} with cell size {}",
+                    area, cell_size
+                );
+                assert_eq!(
+                    grid.any_near(&area, |ind| rects[ind].check_collision_recs(&area)),
+                    !expected.is_empty()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+
+use crate::{Rocket, RocketState};
+
+// New columns go on the end so scripts reading older files by position keep working
+const HEADER: &str = "generation,min_fitness,max_fitness,mean_fitness,median_fitness,alive_count,\
+                      successful_count,timeout_count";
+
+pub struct GenerationStats {
+    pub generation: u32,
+    pub min_fitness: f32,
+    pub max_fitness: f32,
+    pub mean_fitness: f32,
+    pub median_fitness: f32,
+    // Rockets that neither crashed nor landed, which includes every timed out one
+    pub alive_count: usize,
+    pub successful_count: usize,
+    pub timeout_count: usize,
+}
+
+impl GenerationStats {
+    pub fn from_rockets(generation: u32, rockets: &[Rocket]) -> Self {
+        let mut fitnesses: Vec<f32> = rockets.iter().map(|el| el.dna.fitness).collect();
+        fitnesses.sort_by(f32::total_cmp);
+
+        let len = fitnesses.len();
+        let median_fitness = if len == 0 {
+            0.0
+        } else if len.is_multiple_of(2) {
+            (fitnesses[len / 2 - 1] + fitnesses[len / 2]) / 2.0
+        } else {
+            fitnesses[len / 2]
+        };
+        let count_state = |state| rockets.iter().filter(|el| el.state == state).count();
+
+        Self {
+            generation,
+            min_fitness: fitnesses.first().copied().unwrap_or(0.0),
+            max_fitness: fitnesses.last().copied().unwrap_or(0.0),
+            mean_fitness: fitnesses.iter().sum::<f32>() / len.max(1) as f32,
+            median_fitness,
+            alive_count: count_state(RocketState::Alive) + count_state(RocketState::Timeout),
+            successful_count: count_state(RocketState::Successful),
+            timeout_count: count_state(RocketState::Timeout),
+        }
+    }
+}
+
+pub struct StatsWriter {
+    writer: BufWriter<File>,
+}
+
+impl StatsWriter {
+    pub fn create(path: &str) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", HEADER)?;
+        Ok(Self { writer })
+    }
+
+    // Keeps the rows already in `path`, only writing the header if it was empty
+    pub fn append(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        let mut writer = BufWriter::new(file);
+        if is_empty {
+            writeln!(writer, "{}", HEADER)?;
+        }
+        Ok(Self { writer })
+    }
+
+    pub fn write(&mut self, stats: &GenerationStats) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{},{}",
+            stats.generation,
+            stats.min_fitness,
+            stats.max_fitness,
+            stats.mean_fitness,
+            stats.median_fitness,
+            stats.alive_count,
+            stats.successful_count,
+            stats.timeout_count
+        )
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_match_the_header() {
+        let path = std::env::temp_dir().join("smart_rockets_stats_test.csv");
+        let path = path.to_str().unwrap();
+        let mut writer = StatsWriter::create(path).unwrap();
+        writer
+            .write(&GenerationStats::from_rockets(3, &[]))
+            .unwrap();
+        writer.flush().unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        let mut lines = contents.lines();
+        let header: Vec<_> = lines.next().unwrap().split(',').collect();
+        assert_eq!(
+            header[5..],
+            ["alive_count", "successful_count", "timeout_count"]
+        );
+        assert_eq!(lines.next().unwrap().split(',').count(), header.len());
+    }
+}